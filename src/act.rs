@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+use crate::{
+    buff::{Buff, ParseError},
+    sexpr::Sexpr,
+};
+
+/// Action formulas, describing the sets of actions a modality ranges over
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Act<A> {
+    /// Any action
+    True,
+    /// A single action
+    One(A),
+    /// Any of the given actions
+    Set(Vec<A>),
+    /// Any action not matched by the formula
+    Not(Box<Act<A>>),
+    And(Box<Act<A>>, Box<Act<A>>),
+    Or(Box<Act<A>>, Box<Act<A>>),
+}
+
+impl<A> Act<A>
+where
+    A: Eq,
+{
+    /// Check if an action matches the formula
+    pub fn matches(&self, a: &A) -> bool {
+        match self {
+            Act::True => true,
+            Act::One(b) => a == b,
+            Act::Set(actions) => actions.contains(a),
+            Act::Not(f) => !f.matches(a),
+            Act::And(f, g) => f.matches(a) && g.matches(a),
+            Act::Or(f, g) => f.matches(a) || g.matches(a),
+        }
+    }
+
+    /// The actions mentioned by the formula
+    pub fn actions(&self) -> Vec<&A> {
+        match self {
+            Act::True => vec![],
+            Act::One(a) => vec![a],
+            Act::Set(actions) => actions.iter().collect(),
+            Act::Not(f) => f.actions(),
+            Act::And(f, g) | Act::Or(f, g) => {
+                let mut actions = f.actions();
+                actions.extend(g.actions());
+                actions
+            }
+        }
+    }
+}
+
+impl Act<String> {
+    fn parse_binop(buff: &mut Buff<Sexpr>, is_or: bool) -> Result<Self, ParseError> {
+        let args = buff.convert_list(Self::from_sexpr)?;
+        let res = if is_or {
+            args.into_iter()
+                .reduce(|lhs, rhs| Act::Or(Box::new(lhs), Box::new(rhs)))
+        } else {
+            args.into_iter()
+                .reduce(|lhs, rhs| Act::And(Box::new(lhs), Box::new(rhs)))
+        };
+        Ok(res.unwrap())
+    }
+
+    /// Read an action formula: `true`, an action `a` (or `(a)`),
+    /// `(set a b ...)`, `(not f)`, `(and f g ...)` or `(or f g ...)`
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        match sexpr {
+            Sexpr::Sym(s, _) if s == "true" => Ok(Act::True),
+            Sexpr::Sym(s, _) => Ok(Act::One(s)),
+            Sexpr::Num(_, _) | Sexpr::Str(_, _) => Err(sexpr.error("an action formula")),
+            Sexpr::List(_, _) => {
+                if let Some(single) = sexpr.clone().get_singleton_opt() {
+                    if single.is_symb() {
+                        return Self::from_sexpr(single);
+                    }
+                }
+                let mut buff = sexpr.into_buff("an action formula")?;
+                let pos = buff.position();
+                let op = buff.expect_symb()?;
+                match op.as_str() {
+                    "set" => {
+                        let mut actions = vec![buff.expect_symb()?];
+                        while !buff.is_empty() {
+                            actions.push(buff.expect_symb()?);
+                        }
+                        Ok(Act::Set(actions))
+                    }
+                    "not" => {
+                        let f = buff.expect_convert(Self::from_sexpr)?;
+                        buff.expect_end()?;
+                        Ok(Act::Not(Box::new(f)))
+                    }
+                    "and" | "or" => Self::parse_binop(&mut buff, op == "or"),
+                    _ => Err(ParseError::new(pos, "an action operator", op)),
+                }
+            }
+        }
+    }
+}
+
+impl<A> Display for Act<A>
+where
+    A: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Act::True => write!(f, "true"),
+            Act::One(a) => write!(f, "{}", a),
+            Act::Set(actions) => {
+                let actions = actions
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(f, "{{{}}}", actions)
+            }
+            Act::Not(a) => write!(f, "!{}", a),
+            Act::And(a, b) => write!(f, "({} && {})", a, b),
+            Act::Or(a, b) => write!(f, "({} || {})", a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{act::Act, sexpr::Sexpr};
+
+    #[test]
+    fn test_1() {
+        let sexpr = "(and (not (set a b)) (or c a))".parse::<Sexpr>().unwrap();
+        let act = Act::from_sexpr(sexpr).unwrap();
+        assert!(act.matches(&"c".to_string()));
+        assert!(!act.matches(&"a".to_string()));
+        assert!(!act.matches(&"d".to_string()));
+        assert_eq!(act.to_string(), "(!{a b} && (c || a))");
+        let sexpr = "(tau)".parse::<Sexpr>().unwrap();
+        assert_eq!(Act::from_sexpr(sexpr), Ok(Act::One("tau".to_string())));
+    }
+}
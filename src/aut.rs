@@ -0,0 +1,215 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{
+    buff::{Buff, ParseError, Pos},
+    spec::Spec,
+    ts::Ts,
+};
+
+/// Prefix of the actions of the self-loops encoding the propositions
+/// labelling a state in the Aldebaran format (e.g. `"prop:P"`)
+pub const PROP_PREFIX: &str = "prop:";
+
+/// Skip spaces and line breaks
+fn skip_blanks(buff: &mut Buff<char>) {
+    while buff.top().is_some_and(char::is_whitespace) {
+        buff.pop();
+    }
+}
+
+fn expect_char(buff: &mut Buff<char>, c: char) -> Result<(), ParseError> {
+    skip_blanks(buff);
+    buff.expect(c)
+}
+
+/// A number, located by its position
+fn expect_num(buff: &mut Buff<char>) -> Result<(u32, Pos), ParseError> {
+    skip_blanks(buff);
+    let pos = buff.position();
+    Ok((buff.expect_u32()?, pos))
+}
+
+/// An action, either quoted or running until the last comma of the line
+fn expect_label(buff: &mut Buff<char>) -> Result<String, ParseError> {
+    skip_blanks(buff);
+    let mut label = String::new();
+    if buff.top() == Some('"') {
+        buff.pop();
+        while let Some(c) = buff.top().filter(|c| *c != '"' && *c != '\n') {
+            buff.pop();
+            label.push(c);
+        }
+        buff.expect('"')?;
+    } else {
+        // The label runs until the last comma of the line
+        buff.save();
+        let mut line = vec![];
+        while let Some(c) = buff.top().filter(|c| *c != '\n') {
+            buff.pop();
+            line.push(c);
+        }
+        buff.restore();
+        let len = line.iter().rposition(|c| *c == ',').unwrap_or(0);
+        for c in line.into_iter().take(len) {
+            buff.pop();
+            label.push(c);
+        }
+        label = label.trim_end().to_string();
+    }
+    if label.is_empty() {
+        return Err(buff.error("an action"));
+    }
+    Ok(label)
+}
+
+impl Ts<String, String> {
+    /// Read a system in the Aldebaran format:
+    /// a header `des (init, ntrans, nstates)` followed by `ntrans` transitions
+    /// `(from, "action", to)` between the states `0` to `nstates - 1`.
+    /// Self-loops through an action `prop:P` label their state with the proposition `P`.
+    pub fn from_aut(text: &str) -> Result<Self, ParseError> {
+        let mut buff = Buff::from_text(text);
+        skip_blanks(&mut buff);
+        for c in "des".chars() {
+            buff.expect(c)?;
+        }
+        expect_char(&mut buff, '(')?;
+        let (init, init_pos) = expect_num(&mut buff)?;
+        expect_char(&mut buff, ',')?;
+        let (ntrans, _) = expect_num(&mut buff)?;
+        expect_char(&mut buff, ',')?;
+        let (nstates, _) = expect_num(&mut buff)?;
+        expect_char(&mut buff, ')')?;
+        let check_state = |(s, pos): (u32, Pos)| {
+            if s < nstates {
+                Ok(s)
+            } else {
+                let expected = format!("a state below {}", nstates);
+                Err(ParseError::new(pos, expected, s))
+            }
+        };
+        let init = check_state((init, init_pos))?;
+        let mut labels = HashMap::<u32, Vec<String>>::new();
+        let mut transitions = HashMap::<u32, Vec<(String, u32)>>::new();
+        let mut count = 0;
+        skip_blanks(&mut buff);
+        while !buff.is_empty() {
+            expect_char(&mut buff, '(')?;
+            let from = check_state(expect_num(&mut buff)?)?;
+            expect_char(&mut buff, ',')?;
+            let action = expect_label(&mut buff)?;
+            expect_char(&mut buff, ',')?;
+            let to = check_state(expect_num(&mut buff)?)?;
+            expect_char(&mut buff, ')')?;
+            skip_blanks(&mut buff);
+            count += 1;
+            match action.strip_prefix(PROP_PREFIX) {
+                Some(prop) if from == to => labels.entry(from).or_default().push(prop.to_string()),
+                _ => transitions.entry(from).or_default().push((action, to)),
+            }
+        }
+        if count != ntrans {
+            let expected = format!("{} transitions", ntrans);
+            let found = format!("{} transitions", count);
+            return Err(ParseError::new(buff.position(), expected, found));
+        }
+        Ok(Ts::new::<Spec<String, String>>(
+            (0..nstates).collect(),
+            vec![init],
+            labels.into_iter().collect(),
+            transitions.into_iter().collect(),
+            vec![],
+        ))
+    }
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Write the system in the Aldebaran format.
+    /// The states are numbered from 0, which is the initial state,
+    /// and the labels of the states are encoded as self-loops.
+    /// Fails if the system does not have a single initial state.
+    pub fn to_aut(&self) -> Result<String, String> {
+        let [init] = self.initial.iter().copied().collect::<Vec<_>>()[..] else {
+            return Err(format!(
+                "the Aldebaran format requires a single initial state, found {}",
+                self.initial.len()
+            ));
+        };
+        let mut states = self.states.iter().copied().collect::<Vec<_>>();
+        states.sort_by_key(|s| (*s != init, *s));
+        let ids = states
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (*s, i))
+            .collect::<HashMap<_, _>>();
+        let ids = &ids;
+        let mut lines = vec![];
+        for s in states.iter() {
+            let mut label = self
+                .label(s)
+                .iter()
+                .map(|p| format!("{}{}", PROP_PREFIX, p))
+                .collect::<Vec<_>>();
+            label.sort();
+            for action in label {
+                lines.push(format!("({}, \"{}\", {})", ids[s], action, ids[s]));
+            }
+            let mut post = self
+                .transitions
+                .post(s)
+                .map(|(a, t)| (a.to_string(), ids[t]))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            post.sort();
+            for (a, t) in post {
+                lines.push(format!("({}, \"{}\", {})", ids[s], a, t));
+            }
+        }
+        let header = format!("des (0, {}, {})", lines.len(), states.len());
+        lines.insert(0, header);
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        buff::{ParseError, Pos},
+        ts::Ts,
+    };
+
+    #[test]
+    fn test_1() {
+        let text =
+            "des (0, 4, 3)\n(0, \"a\", 1)\n(1, b(1, 2), 2)\n(2,\"prop:P\",2)\n(2, \"tau\", 0)\n";
+        let ts = Ts::from_aut(text).unwrap();
+        assert_eq!(ts.states, HashSet::from([0, 1, 2]));
+        assert_eq!(ts.label(&2), HashSet::from(["P".to_string()]));
+        assert_eq!(ts.succ(&1, &"b(1, 2)".to_string()).count(), 1);
+        let back = Ts::from_aut(&ts.to_aut().unwrap()).unwrap();
+        assert!(ts.bisimilar(&back).is_ok());
+    }
+
+    #[test]
+    fn test_2() {
+        assert_eq!(
+            Ts::from_aut("des (0, 1, 2)\n(0, a, 2)").unwrap_err(),
+            ParseError::new(Pos { line: 2, col: 8 }, "a state below 2", 2)
+        );
+        assert_eq!(
+            Ts::from_aut("des (0, 2, 2)\n(0, a, 1)\n").unwrap_err(),
+            ParseError::new(Pos { line: 3, col: 1 }, "2 transitions", "1 transitions")
+        );
+    }
+}
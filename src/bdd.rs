@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+
+/// A reduced ordered binary decision diagram, identified by the index
+/// of its root in its [`Manager`]
+pub type Bdd = u32;
+
+pub const FALSE: Bdd = 0;
+pub const TRUE: Bdd = 1;
+
+/// Variable of the terminal nodes, greater than every variable
+const TERMINAL: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Node {
+    var: u32,
+    low: Bdd,
+    high: Bdd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Op {
+    And,
+    Or,
+    Xor,
+    Exists,
+    AndExists,
+}
+
+/// Shared store of BDDs: equal functions are represented by the same node,
+/// and the results of the operations are cached.
+/// Variables are ordered by their number, smaller numbers being closer to the root.
+pub struct Manager {
+    nodes: Vec<Node>,
+    unique: HashMap<Node, Bdd>,
+    cache: HashMap<(Op, Bdd, Bdd, Bdd), Bdd>,
+}
+
+impl Default for Manager {
+    fn default() -> Self {
+        let terminal = |b| Node {
+            var: TERMINAL,
+            low: b,
+            high: b,
+        };
+        Manager {
+            nodes: vec![terminal(FALSE), terminal(TRUE)],
+            unique: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+}
+
+impl Manager {
+    fn mk(&mut self, var: u32, low: Bdd, high: Bdd) -> Bdd {
+        if low == high {
+            return low;
+        }
+        let node = Node { var, low, high };
+        if let Some(f) = self.unique.get(&node) {
+            return *f;
+        }
+        let f = self.nodes.len() as Bdd;
+        self.nodes.push(node);
+        self.unique.insert(node, f);
+        f
+    }
+
+    fn top(&self, f: Bdd) -> u32 {
+        self.nodes[f as usize].var
+    }
+
+    /// The cofactors of `f` for the values of a variable at least as low as its root
+    fn cofactors(&self, f: Bdd, var: u32) -> (Bdd, Bdd) {
+        let node = self.nodes[f as usize];
+        if node.var == var {
+            (node.low, node.high)
+        } else {
+            (f, f)
+        }
+    }
+
+    /// Number of nodes created so far
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The function holding when a variable is true
+    pub fn var(&mut self, var: u32) -> Bdd {
+        self.mk(var, FALSE, TRUE)
+    }
+
+    pub fn not(&mut self, f: Bdd) -> Bdd {
+        self.apply(Op::Xor, f, TRUE)
+    }
+
+    pub fn and(&mut self, f: Bdd, g: Bdd) -> Bdd {
+        self.apply(Op::And, f, g)
+    }
+
+    pub fn or(&mut self, f: Bdd, g: Bdd) -> Bdd {
+        self.apply(Op::Or, f, g)
+    }
+
+    pub fn iff(&mut self, f: Bdd, g: Bdd) -> Bdd {
+        let xor = self.apply(Op::Xor, f, g);
+        self.not(xor)
+    }
+
+    fn apply(&mut self, op: Op, f: Bdd, g: Bdd) -> Bdd {
+        let terminal = match op {
+            Op::And if f == FALSE || g == FALSE => Some(FALSE),
+            Op::And if f == TRUE || f == g => Some(g),
+            Op::And if g == TRUE => Some(f),
+            Op::Or if f == TRUE || g == TRUE => Some(TRUE),
+            Op::Or if f == FALSE || f == g => Some(g),
+            Op::Or if g == FALSE => Some(f),
+            Op::Xor if f == g => Some(FALSE),
+            Op::Xor if f == FALSE => Some(g),
+            Op::Xor if g == FALSE => Some(f),
+            _ => None,
+        };
+        if let Some(res) = terminal {
+            return res;
+        }
+        // All the operations are commutative
+        let key = (op, f.min(g), f.max(g), 0);
+        if let Some(res) = self.cache.get(&key) {
+            return *res;
+        }
+        let var = self.top(f).min(self.top(g));
+        let (f0, f1) = self.cofactors(f, var);
+        let (g0, g1) = self.cofactors(g, var);
+        let low = self.apply(op, f0, g0);
+        let high = self.apply(op, f1, g1);
+        let res = self.mk(var, low, high);
+        self.cache.insert(key, res);
+        res
+    }
+
+    /// The conjunction of the given variables, used to quantify them
+    pub fn cube(&mut self, vars: &[u32]) -> Bdd {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.iter()
+            .rev()
+            .fold(TRUE, |acc, var| self.mk(*var, FALSE, acc))
+    }
+
+    /// Skip the variables of a cube which are above a given variable
+    fn skip(&self, mut cube: Bdd, var: u32) -> Bdd {
+        while self.top(cube) < var {
+            cube = self.nodes[cube as usize].high;
+        }
+        cube
+    }
+
+    /// Existential quantification of the variables of a cube
+    pub fn exists(&mut self, f: Bdd, cube: Bdd) -> Bdd {
+        let var = self.top(f);
+        let cube = self.skip(cube, var);
+        if f <= TRUE || cube == TRUE {
+            return f;
+        }
+        let key = (Op::Exists, f, cube, 0);
+        if let Some(res) = self.cache.get(&key) {
+            return *res;
+        }
+        let (f0, f1) = self.cofactors(f, var);
+        let res = if self.top(cube) == var {
+            let rest = self.nodes[cube as usize].high;
+            let low = self.exists(f0, rest);
+            let high = self.exists(f1, rest);
+            self.or(low, high)
+        } else {
+            let low = self.exists(f0, cube);
+            let high = self.exists(f1, cube);
+            self.mk(var, low, high)
+        };
+        self.cache.insert(key, res);
+        res
+    }
+
+    /// Relational product: `∃ cube. f ∧ g`, without building `f ∧ g`
+    pub fn and_exists(&mut self, f: Bdd, g: Bdd, cube: Bdd) -> Bdd {
+        if f == FALSE || g == FALSE {
+            return FALSE;
+        }
+        if f == TRUE {
+            return self.exists(g, cube);
+        }
+        if g == TRUE {
+            return self.exists(f, cube);
+        }
+        let var = self.top(f).min(self.top(g));
+        let cube = self.skip(cube, var);
+        if cube == TRUE {
+            return self.and(f, g);
+        }
+        let key = (Op::AndExists, f.min(g), f.max(g), cube);
+        if let Some(res) = self.cache.get(&key) {
+            return *res;
+        }
+        let (f0, f1) = self.cofactors(f, var);
+        let (g0, g1) = self.cofactors(g, var);
+        let res = if self.top(cube) == var {
+            let rest = self.nodes[cube as usize].high;
+            let low = self.and_exists(f0, g0, rest);
+            if low == TRUE {
+                TRUE
+            } else {
+                let high = self.and_exists(f1, g1, rest);
+                self.or(low, high)
+            }
+        } else {
+            let low = self.and_exists(f0, g0, cube);
+            let high = self.and_exists(f1, g1, cube);
+            self.mk(var, low, high)
+        };
+        self.cache.insert(key, res);
+        res
+    }
+
+    /// Rename the variables of a function.
+    /// The renaming must preserve the order of the variables it depends on.
+    pub fn rename<F>(&mut self, f: Bdd, map: &F) -> Bdd
+    where
+        F: Fn(u32) -> u32,
+    {
+        self.rename_with(f, map, &mut HashMap::new())
+    }
+
+    fn rename_with<F>(&mut self, f: Bdd, map: &F, memo: &mut HashMap<Bdd, Bdd>) -> Bdd
+    where
+        F: Fn(u32) -> u32,
+    {
+        if f <= TRUE {
+            return f;
+        }
+        if let Some(res) = memo.get(&f) {
+            return *res;
+        }
+        let node = self.nodes[f as usize];
+        let low = self.rename_with(node.low, map, memo);
+        let high = self.rename_with(node.high, map, memo);
+        let res = self.mk(map(node.var), low, high);
+        memo.insert(f, res);
+        res
+    }
+
+    /// Number of satisfying assignments of the variables `0..vars`
+    pub fn count(&self, f: Bdd, vars: u32) -> f64 {
+        let above = self.top(f).min(vars);
+        self.count_below(f, vars, &mut HashMap::new()) * 2f64.powi(above as i32)
+    }
+
+    /// Number of satisfying assignments of the variables
+    /// from the root of `f` to `vars`
+    fn count_below(&self, f: Bdd, vars: u32, memo: &mut HashMap<Bdd, f64>) -> f64 {
+        if f <= TRUE {
+            return f as f64;
+        }
+        if let Some(n) = memo.get(&f) {
+            return *n;
+        }
+        let node = self.nodes[f as usize];
+        let mut n = 0.0;
+        for g in [node.low, node.high] {
+            let skipped = self.top(g).min(vars) - node.var - 1;
+            n += self.count_below(g, vars, memo) * 2f64.powi(skipped as i32);
+        }
+        memo.insert(f, n);
+        n
+    }
+
+    /// The values of the variables along a path to `TRUE`,
+    /// or `None` if the function is unsatisfiable
+    pub fn pick(&self, f: Bdd) -> Option<Vec<(u32, bool)>> {
+        if f == FALSE {
+            return None;
+        }
+        let mut path = vec![];
+        let mut f = f;
+        while f != TRUE {
+            let node = self.nodes[f as usize];
+            let value = node.low == FALSE;
+            path.push((node.var, value));
+            f = if value { node.high } else { node.low };
+        }
+        Some(path)
+    }
+}
+
+/// A vector of functions standing for the bits of an integer in two's
+/// complement, the least significant first: the last bit is the sign
+pub type Word = Vec<Bdd>;
+
+/// The word sign-extended to (at least) `len` bits
+pub fn extend(word: &[Bdd], len: usize) -> Word {
+    let sign = word.last().copied().unwrap_or(FALSE);
+    let mut word = word.to_vec();
+    word.resize(len.max(word.len()), sign);
+    word
+}
+
+/// Arithmetic circuits on words. The words of the results are long
+/// enough for the results to be exact, except for [`Manager::fit`]
+impl Manager {
+    pub fn xor(&mut self, f: Bdd, g: Bdd) -> Bdd {
+        self.apply(Op::Xor, f, g)
+    }
+
+    /// `if c then f else g`
+    pub fn ite(&mut self, c: Bdd, f: Bdd, g: Bdd) -> Bdd {
+        let then = self.and(c, f);
+        let not_c = self.not(c);
+        let otherwise = self.and(not_c, g);
+        self.or(then, otherwise)
+    }
+
+    /// The constant word of an integer
+    pub fn constant(&self, n: i64) -> Word {
+        let len = 65 - if n < 0 { !n } else { n }.leading_zeros() as usize;
+        (0..len)
+            .map(|k| if n >> k & 1 == 1 { TRUE } else { FALSE })
+            .collect()
+    }
+
+    /// The word of a non-negative integer given by its bits
+    pub fn unsigned(&self, bits: &[Bdd]) -> Word {
+        let mut word = bits.to_vec();
+        word.push(FALSE);
+        word
+    }
+
+    /// The sum of two words of the same length, modulo the length
+    fn adder(&mut self, a: &[Bdd], b: &[Bdd], mut carry: Bdd) -> Word {
+        let mut sum = vec![];
+        for (x, y) in a.iter().zip(b.iter()) {
+            let xy = self.xor(*x, *y);
+            sum.push(self.xor(xy, carry));
+            let both = self.and(*x, *y);
+            let propagated = self.and(xy, carry);
+            carry = self.or(both, propagated);
+        }
+        sum
+    }
+
+    pub fn add(&mut self, a: &[Bdd], b: &[Bdd]) -> Word {
+        let len = a.len().max(b.len()) + 1;
+        self.adder(&extend(a, len), &extend(b, len), FALSE)
+    }
+
+    pub fn sub(&mut self, a: &[Bdd], b: &[Bdd]) -> Word {
+        let len = a.len().max(b.len()) + 1;
+        let not_b = extend(b, len)
+            .into_iter()
+            .map(|x| self.not(x))
+            .collect::<Vec<_>>();
+        self.adder(&extend(a, len), &not_b, TRUE)
+    }
+
+    pub fn neg(&mut self, a: &[Bdd]) -> Word {
+        self.sub(&[], a)
+    }
+
+    /// The product, computed by adding the shifted partial products
+    pub fn mul(&mut self, a: &[Bdd], b: &[Bdd]) -> Word {
+        let len = a.len() + b.len();
+        let (a, b) = (extend(a, len), extend(b, len));
+        let mut product = vec![FALSE; len];
+        for (k, y) in b.iter().enumerate() {
+            let mut partial = vec![FALSE; k];
+            partial.extend(a[..len - k].iter().map(|x| self.and(*x, *y)));
+            product = self.adder(&product, &partial, FALSE);
+        }
+        product
+    }
+
+    /// `if c then a else b`, bitwise
+    fn select(&mut self, c: Bdd, a: &[Bdd], b: &[Bdd]) -> Word {
+        let len = a.len().max(b.len());
+        let (a, b) = (extend(a, len), extend(b, len));
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| self.ite(c, *x, *y))
+            .collect()
+    }
+
+    pub fn abs(&mut self, a: &[Bdd]) -> Word {
+        let neg = self.neg(a);
+        self.select(*a.last().unwrap_or(&FALSE), &neg, a)
+    }
+
+    /// The quotient rounded towards zero, computed by long division of
+    /// the absolute values. Dividing by zero gives an arbitrary word
+    pub fn div(&mut self, a: &[Bdd], b: &[Bdd]) -> Word {
+        let (abs_a, abs_b) = (self.abs(a), self.abs(b));
+        let mut quotient = vec![FALSE; abs_a.len()];
+        let mut rest = vec![];
+        for k in (0..abs_a.len()).rev() {
+            rest.insert(0, abs_a[k]);
+            let diff = self.sub(&rest, &abs_b);
+            let fits = self.not(*diff.last().unwrap());
+            rest = self.select(fits, &diff, &rest);
+            // The rest is a non-negative integer below |b|
+            rest.truncate(abs_b.len());
+            quotient[k] = fits;
+        }
+        let negative = {
+            let (sign_a, sign_b) = (a.last().unwrap_or(&FALSE), b.last().unwrap_or(&FALSE));
+            self.xor(*sign_a, *sign_b)
+        };
+        let neg = self.neg(&quotient);
+        self.select(negative, &neg, &quotient)
+    }
+
+    pub fn eq(&mut self, a: &[Bdd], b: &[Bdd]) -> Bdd {
+        let len = a.len().max(b.len());
+        let (a, b) = (extend(a, len), extend(b, len));
+        a.iter().zip(b.iter()).fold(TRUE, |acc, (x, y)| {
+            let same = self.iff(*x, *y);
+            self.and(acc, same)
+        })
+    }
+
+    pub fn lt(&mut self, a: &[Bdd], b: &[Bdd]) -> Bdd {
+        *self.sub(a, b).last().unwrap()
+    }
+
+    /// The word truncated to `len` bits, and the condition under which
+    /// its value does not fit in them
+    pub fn fit(&mut self, a: &[Bdd], len: usize) -> (Word, Bdd) {
+        if a.len() <= len {
+            return (a.to_vec(), FALSE);
+        }
+        let sign = a[len - 1];
+        let overflow = a[len..].iter().fold(FALSE, |acc, x| {
+            let differs = self.xor(*x, sign);
+            self.or(acc, differs)
+        });
+        (a[..len].to_vec(), overflow)
+    }
+
+    /// The value of a word when all its bits are constant
+    pub fn value(&self, a: &[Bdd]) -> Option<i64> {
+        let mut n = 0i128;
+        for (k, x) in a.iter().enumerate() {
+            let bit = match *x {
+                FALSE => 0,
+                TRUE => 1,
+                _ => return None,
+            };
+            n |= bit << k;
+        }
+        if a.last() == Some(&TRUE) {
+            n -= 1 << a.len();
+        }
+        i64::try_from(n).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bdd, Manager, Word, FALSE, TRUE};
+
+    #[test]
+    fn test_1() {
+        let mut m = Manager::default();
+        let (x, y) = (m.var(0), m.var(1));
+        let xy = m.and(x, y);
+        let not_x = m.not(x);
+        let not_y = m.not(y);
+        let nor = m.and(not_x, not_y);
+        let or = m.or(x, y);
+        assert_eq!(m.not(nor), or);
+        assert_eq!(m.and(xy, not_x), FALSE);
+        assert_eq!(m.iff(x, x), TRUE);
+        assert_eq!(m.count(or, 3), 6.0);
+        let cube = m.cube(&[1]);
+        assert_eq!(m.exists(xy, cube), x);
+        assert_eq!(m.pick(xy), Some(vec![(0, true), (1, true)]));
+    }
+
+    #[test]
+    fn test_2() {
+        let mut m = Manager::default();
+        // A relation from x0 x2 to their copies x1 x3: (x0, x2) -> (x2, x0)
+        let vars = (0..4).map(|i| m.var(i)).collect::<Vec<_>>();
+        let swap0 = m.iff(vars[1], vars[2]);
+        let swap1 = m.iff(vars[3], vars[0]);
+        let relation = m.and(swap0, swap1);
+        let not_x2 = m.not(vars[2]);
+        let from = m.and(vars[0], not_x2);
+        let cube = m.cube(&[0, 2]);
+        let image = m.and_exists(from, relation, cube);
+        let image = m.rename(image, &|var| var - 1);
+        let not_x0 = m.not(vars[0]);
+        assert_eq!(image, m.and(not_x0, vars[2]));
+        let product = m.and(from, relation);
+        assert_eq!(m.exists(product, cube), m.and_exists(relation, from, cube));
+    }
+
+    #[test]
+    fn test_3() {
+        let mut m = Manager::default();
+        type Circuit = fn(&mut Manager, &[Bdd], &[Bdd]) -> Word;
+        type Op = fn(i64, i64) -> i64;
+        let ops: [(Op, Circuit); 4] = [
+            (|a, b| a + b, Manager::add),
+            (|a, b| a - b, Manager::sub),
+            (|a, b| a * b, Manager::mul),
+            (|a, b| a / b, Manager::div),
+        ];
+        for a in [-9, -8, -1, 0, 1, 7, 300] {
+            for b in [-3, -1, 1, 2, 64] {
+                let (x, y) = (m.constant(a), m.constant(b));
+                for (op, circuit) in ops {
+                    let res = circuit(&mut m, &x, &y);
+                    assert_eq!(m.value(&res), Some(op(a, b)));
+                }
+                assert_eq!(m.lt(&x, &y), if a < b { TRUE } else { FALSE });
+                assert_eq!(m.eq(&x, &y), if a == b { TRUE } else { FALSE });
+            }
+        }
+        // x + 1 over a 2-bit unsigned variable
+        let x = [m.var(0), m.var(1)];
+        let x = m.unsigned(&x);
+        let one = m.constant(1);
+        let sum = m.add(&x, &one);
+        let four = m.constant(4);
+        let max = m.and(x[0], x[1]);
+        assert_eq!(m.eq(&sum, &four), max);
+        let (_, overflow) = m.fit(&sum, 3);
+        assert_eq!(overflow, max);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{dense::Transitions, graph, spec::Spec, ts::Ts};
+
+/// A partition of the states of a transition system,
+/// mapping every state to the number of its block
+pub type Partition = HashMap<u32, usize>;
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Partition of the states according to their labels
+    fn label_partition(&self) -> Partition {
+        let props = self
+            .labels
+            .keys()
+            .cloned()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect::<HashMap<P, usize>>();
+        let mut blocks = HashMap::new();
+        self.states
+            .iter()
+            .map(|s| {
+                let mut label = self.label(s).iter().map(|p| props[p]).collect::<Vec<_>>();
+                label.sort();
+                let n = blocks.len();
+                (*s, *blocks.entry(label).or_insert(n))
+            })
+            .collect()
+    }
+
+    /// Refine a partition until every block is stable:
+    /// two states stay in the same block if they have the same signature.
+    /// Returns the successive partitions, the last one being stable.
+    fn refine<F>(&self, partition: Partition, signatures: F) -> Vec<Partition>
+    where
+        F: Fn(&Partition) -> HashMap<u32, Vec<(usize, usize)>>,
+    {
+        let mut count = partition.values().collect::<HashSet<_>>().len();
+        let mut levels = vec![partition];
+        loop {
+            let partition = levels.last().unwrap();
+            let mut blocks = HashMap::new();
+            let refined = signatures(partition)
+                .into_iter()
+                .map(|(s, mut sig)| {
+                    sig.sort();
+                    sig.dedup();
+                    let n = blocks.len();
+                    (s, *blocks.entry((partition[&s], sig)).or_insert(n))
+                })
+                .collect();
+            levels.push(refined);
+            if blocks.len() == count {
+                return levels;
+            }
+            count = blocks.len();
+        }
+    }
+
+    fn action_index(&self) -> HashMap<A, usize> {
+        self.actions()
+            .into_iter()
+            .enumerate()
+            .map(|(i, a)| (a, i))
+            .collect()
+    }
+
+    /// The coarsest strong bisimulation of the system, computed by signature refinement.
+    ///
+    /// Bisimilar states have the same labels and, for every transition of one of them,
+    /// the other has a transition through the same action to a bisimilar state.
+    pub fn strong_partition(&self) -> Partition {
+        self.strong_refinements().pop().unwrap()
+    }
+
+    /// The successive partitions computed by the refinement of the strong bisimulation:
+    /// states in different blocks of the i-th partition are told apart by a formula
+    /// nesting at most i modalities.
+    pub(crate) fn strong_refinements(&self) -> Vec<Partition> {
+        let index = self.action_index();
+        self.refine(self.label_partition(), |partition| {
+            self.states
+                .iter()
+                .map(|s| {
+                    let sig = self
+                        .transitions
+                        .post(s)
+                        .map(|(a, t)| (index[a], partition[t]))
+                        .collect();
+                    (*s, sig)
+                })
+                .collect()
+        })
+    }
+
+    /// States reachable from `s` through silent transitions inside its block
+    fn inert_closure(&self, s: u32, tau: &A, partition: &Partition) -> HashSet<u32> {
+        let mut seen = HashSet::from([s]);
+        let mut queue = VecDeque::from([s]);
+        while let Some(x) = queue.pop_front() {
+            for y in self.succ(&x, tau) {
+                if partition[y] == partition[&s] && seen.insert(*y) {
+                    queue.push_back(*y);
+                }
+            }
+        }
+        seen
+    }
+
+    /// States lying on a cycle of silent transitions inside their block
+    fn inert_cycles(&self, tau: &A, partition: &Partition) -> HashSet<u32> {
+        let inert = |x: u32| {
+            self.succ(&x, tau)
+                .filter(|y| partition[*y] == partition[&x])
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        graph::sccs(self.states.iter().copied(), inert)
+            .into_iter()
+            .filter(|c| c.len() > 1 || inert(c[0]).contains(&c[0]))
+            .flatten()
+            .collect()
+    }
+
+    /// The coarsest divergence-sensitive branching bisimulation of the system,
+    /// where `tau` is the silent action.
+    ///
+    /// Silent transitions between equivalent states are not observable,
+    /// except when they can be taken forever.
+    pub fn branching_partition(&self, tau: &A) -> Partition {
+        let index = self.action_index();
+        let silent = index.get(tau).copied().unwrap_or(index.len());
+        self.refine(self.label_partition(), |partition| {
+            let cycles = self.inert_cycles(tau, partition);
+            self.states
+                .iter()
+                .map(|s| {
+                    let closure = self.inert_closure(*s, tau, partition);
+                    let mut sig = closure
+                        .iter()
+                        .flat_map(|x| self.transitions.post(x))
+                        .map(|(a, t)| (index[a], partition[t]))
+                        .filter(|(a, b)| *a != silent || *b != partition[s])
+                        .collect::<Vec<_>>();
+                    if closure.iter().any(|x| cycles.contains(x)) {
+                        sig.push((silent, partition[s]));
+                    }
+                    (*s, sig)
+                })
+                .collect()
+        })
+        .pop()
+        .unwrap()
+    }
+
+    /// The quotient of the system by a partition.
+    ///
+    /// Blocks are numbered from 1 in the order of their smallest state,
+    /// which also gives them their name. Silent transitions inside a block
+    /// are dropped, unless the block is divergent.
+    fn quotient(&self, partition: &Partition, tau: Option<&A>) -> Ts<A, P> {
+        let mut representatives = HashMap::<usize, u32>::new();
+        for (s, b) in partition {
+            let r = representatives.entry(*b).or_insert(*s);
+            *r = (*r).min(*s);
+        }
+        let mut blocks = representatives
+            .iter()
+            .map(|(b, r)| (*r, *b))
+            .collect::<Vec<_>>();
+        blocks.sort();
+        let ids = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, (_, b))| (*b, i as u32 + 1))
+            .collect::<HashMap<usize, u32>>();
+        let id = |s: &u32| ids[&partition[s]];
+        let divergent = match tau {
+            Some(tau) => self.inert_cycles(tau, partition),
+            None => HashSet::new(),
+        };
+        let transitions = self
+            .transitions
+            .iter()
+            .filter(|(s, a, t)| tau != Some(*a) || id(s) != id(t) || divergent.contains(s))
+            .map(|(s, a, t)| (id(s), a.clone(), id(t)));
+        let states = ids.values().copied().collect();
+        let transitions = Transitions::new(&states, transitions);
+        Ts {
+            labels: transitions.to_label_bits(blocks.iter().map(|(r, b)| (ids[b], self.label(r)))),
+            transitions,
+            states,
+            initial: self.initial.iter().map(id).collect(),
+            spec: self.spec.clone(),
+            fairness: self.fairness.clone(),
+            names: blocks
+                .iter()
+                .map(|(r, b)| (ids[b], self.state_name(*r)))
+                .collect(),
+        }
+    }
+
+    /// The quotient of the system under strong bisimulation.
+    ///
+    /// The μ-calculus without modalities over predecessors is invariant under
+    /// bisimulation: such specifications hold in the quotient if and only if
+    /// they hold in the system. Modalities over predecessors are not preserved,
+    /// as bisimilar states may have different predecessors.
+    pub fn minimize(&self) -> Ts<A, P> {
+        self.quotient(&self.strong_partition(), None)
+    }
+
+    /// The quotient of the system under divergence-sensitive branching
+    /// bisimulation, where `tau` is the silent action.
+    ///
+    /// Only the specifications insensitive to silent steps, which use
+    /// neither next-step operators nor modalities over predecessors,
+    /// are preserved.
+    pub fn minimize_branching(&self, tau: &A) -> Ts<A, P> {
+        self.quotient(&self.branching_partition(tau), Some(tau))
+    }
+
+    /// The first specification which may not be preserved by [`Ts::minimize`],
+    /// or by [`Ts::minimize_branching`] if `branching`
+    pub fn unpreserved_spec(&self, branching: bool) -> Option<&Spec<A, P>> {
+        self.spec
+            .iter()
+            .find(|spec| spec.has_converse() || (branching && spec.has_next()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{lang::Prog, spec::Spec, ts::Ts};
+
+    #[test]
+    fn test_1() {
+        // Two copies of a loop a.b, one unfolded twice
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3, 4, 5, 6],
+            vec![1, 3],
+            vec![(2, vec!['P']), (4, vec!['P']), (6, vec!['P'])],
+            vec![
+                (1, vec![('a', 2)]),
+                (2, vec![('b', 1)]),
+                (3, vec![('a', 4)]),
+                (4, vec![('b', 5)]),
+                (5, vec![('a', 6)]),
+                (6, vec![('b', 3)]),
+            ],
+            vec![],
+        );
+        let min = ts.minimize();
+        assert_eq!(min.states, HashSet::from([1, 2]));
+        assert_eq!(min.initial, HashSet::from([1]));
+        assert_eq!(min.label(&2), HashSet::from(['P']));
+    }
+
+    #[test]
+    fn test_2() {
+        // a deadlock is not bisimilar to a state with a successor
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3],
+            vec![1],
+            vec![],
+            vec![(1, vec![('a', 2), ('a', 3)]), (3, vec![('a', 3)])],
+            vec![],
+        );
+        assert_eq!(ts.minimize().states.len(), 3);
+    }
+
+    #[test]
+    fn test_3() {
+        // 1 -t-> 2 -a-> 3 is branching bisimilar to 4 -a-> 5,
+        // 6 -t-> 6 diverges and is not equivalent to the deadlock 3
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3, 4, 5, 6],
+            vec![1, 4],
+            vec![],
+            vec![
+                (1, vec![('t', 2)]),
+                (2, vec![('a', 3)]),
+                (4, vec![('a', 5)]),
+                (6, vec![('t', 6)]),
+            ],
+            vec![],
+        );
+        let partition = ts.branching_partition(&'t');
+        assert_eq!(partition[&1], partition[&4]);
+        assert_eq!(partition[&3], partition[&5]);
+        assert_ne!(partition[&3], partition[&6]);
+        let min = ts.minimize_branching(&'t');
+        assert_eq!(min.states.len(), 3);
+        assert_eq!(min.initial.len(), 1);
+    }
+
+    #[test]
+    fn test_4() {
+        // The quotient satisfies the same specifications as the system
+        let src = "(int x 0 5 0) (rule inc (< x 5) (set x (+ x 1))) (rule reset (= x 5) (set x 0))
+            (rule skip true) (prop Top (= x 5)) (prop Even (= (/ x 2) (/ (+ x 1) 2)))";
+        for (spec, holds) in [
+            ("(spec (ag (ef Top)))", true),
+            ("(spec (ag (ex (skip) (not Top))))", false),
+            ("(spec (ef (and Top Even)))", false),
+        ] {
+            let ts = format!("{} {}", src, spec)
+                .parse::<Prog>()
+                .unwrap()
+                .compile()
+                .unwrap();
+            assert_eq!(ts.check(), holds);
+            assert_eq!(ts.minimize().check(), holds);
+            if ts.unpreserved_spec(true).is_none() {
+                assert_eq!(ts.minimize_branching(&"skip".to_string()).check(), holds);
+            }
+        }
+    }
+
+    #[test]
+    fn test_5() {
+        // Removing the silent step changes the truth of next-step operators
+        let src = "(props P) (actions tau a) (init 1) (trans 1 tau 2) (trans 2 a 3) (label 3 P)";
+        for (spec, preserved) in [
+            ("(spec (any tau true))", false),
+            ("(spec (ex (a) P))", false),
+            ("(spec (x P))", false),
+            ("(spec (ef P))", true),
+            ("(spec (f P))", true),
+        ] {
+            let ts = format!("{} {}", src, spec)
+                .parse::<Prog>()
+                .unwrap()
+                .compile()
+                .unwrap();
+            assert_eq!(ts.unpreserved_spec(true).is_none(), preserved);
+            assert!(ts.unpreserved_spec(false).is_none());
+        }
+    }
+
+    #[test]
+    fn test_6() {
+        // 1 and 2 are merged, though only 1 is labelled
+        let ts = "(props P) (actions a) (init 3) (label 1 P) (trans 1 a 3) (trans 2 a 4)
+            (spec (all-pred a P))"
+            .parse::<Prog>()
+            .unwrap()
+            .compile()
+            .unwrap();
+        assert!(ts.check());
+        assert!(!ts.minimize().check());
+        assert!(ts.unpreserved_spec(false).is_some());
+    }
+}
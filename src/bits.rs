@@ -0,0 +1,123 @@
+/// Sets of integers of a fixed range `0..len`, stored as bit vectors.
+/// Boolean operations are done word by word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// The empty set of integers in `0..len`
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// The set of all integers in `0..len`
+    pub fn full(len: usize) -> Self {
+        let mut set = BitSet {
+            words: vec![u64::MAX; len.div_ceil(64)],
+            len,
+        };
+        set.trim();
+        set
+    }
+
+    /// Clear the bits past the end of the range
+    fn trim(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << (self.len % 64)) - 1;
+            }
+        }
+    }
+
+    pub fn insert(&mut self, i: usize) -> bool {
+        let (word, bit) = (i / 64, 1 << (i % 64));
+        let fresh = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        fresh
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w |= o;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w &= o;
+        }
+    }
+
+    /// The integers of the range which are not in the set
+    pub fn complement(mut self) -> Self {
+        for w in self.words.iter_mut() {
+            *w = !*w;
+        }
+        self.trim();
+        self
+    }
+
+    /// The elements of the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, w)| {
+            let mut w = *w;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+                Some(64 * i + bit)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitSet;
+
+    #[test]
+    fn test_1() {
+        let mut set = BitSet::new(130);
+        assert!(set.insert(3));
+        assert!(set.insert(129));
+        assert!(!set.insert(3));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 129]);
+        let complement = set.clone().complement();
+        assert_eq!(complement.iter().count(), 128);
+        assert!(!complement.contains(129) && complement.contains(128));
+        assert_eq!(complement.complement(), set);
+        assert_eq!(BitSet::full(130), BitSet::new(130).complement());
+    }
+
+    #[test]
+    fn test_2() {
+        let mut a = BitSet::new(70);
+        let mut b = BitSet::new(70);
+        a.insert(1);
+        a.insert(65);
+        b.insert(65);
+        b.insert(69);
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 65, 69]);
+        a.intersect_with(&b);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![65]);
+        a.intersect_with(&BitSet::new(70));
+        assert!(a.is_empty());
+    }
+}
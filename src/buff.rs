@@ -1,8 +1,91 @@
 use std::fmt::Display;
 
+/// Position of an element in a source text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    /// Locate the characters of a text.
+    /// Returns the located characters and the position
+    /// of the end of the text
+    pub fn locate(s: &str) -> (Vec<(char, Pos)>, Pos) {
+        Self::locate_from(s, Pos { line: 1, col: 1 })
+    }
+
+    /// Locate the characters of a text starting at a given position
+    pub fn locate_from(s: &str, start: Pos) -> (Vec<(char, Pos)>, Pos) {
+        let mut pos = start;
+        let mut chars = vec![];
+        for c in s.chars() {
+            chars.push((c, pos));
+            if c == '\n' {
+                pos.line += 1;
+                pos.col = 1;
+            } else {
+                pos.col += 1;
+            }
+        }
+        (chars, pos)
+    }
+}
+
+impl Display for Pos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.col)
+    }
+}
+
+/// Parsing error: what was expected and what was found instead
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub pos: Pos,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new<E: Display, F: Display>(pos: Pos, expected: E, found: F) -> Self {
+        ParseError {
+            pos,
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+
+    /// Combine the errors of two alternatives:
+    /// keep the one that went the furthest in the input
+    pub fn or(self, other: Self) -> Self {
+        match self.pos.cmp(&other.pos) {
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Equal if self.expected == other.expected => self,
+            std::cmp::Ordering::Equal => ParseError {
+                expected: format!("{} or {}", self.expected, other.expected),
+                ..self
+            },
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parse error at {}: expected {}, found {}",
+            self.pos, self.expected, self.found
+        )
+    }
+}
+
 /// Buffer data structure for parsing
 pub struct Buff<T> {
     data: Vec<T>,
+    positions: Vec<Pos>,
+    end: Pos,
+    eof: &'static str,
     pos: usize,
     stack: Vec<usize>,
 }
@@ -13,10 +96,34 @@ where
     T: Eq,
     T: Clone,
 {
-    /// Create a new buffer
+    /// Create a new buffer.
+    /// Elements are located on a single line
     pub fn new(data: Vec<T>) -> Self {
+        let positions = (1..=data.len()).map(|col| Pos { line: 1, col }).collect();
+        let end = Pos {
+            line: 1,
+            col: data.len() + 1,
+        };
+        Buff {
+            data,
+            positions,
+            end,
+            eof: "end of input",
+            pos: 0,
+            stack: vec![0],
+        }
+    }
+
+    /// Create a new buffer from located elements.
+    /// `end` is the position of the end of the buffer
+    /// and `eof` its description in error messages
+    pub fn located(data: Vec<(T, Pos)>, end: Pos, eof: &'static str) -> Self {
+        let (data, positions) = data.into_iter().unzip();
         Buff {
             data,
+            positions,
+            end,
+            eof,
             pos: 0,
             stack: vec![0],
         }
@@ -33,6 +140,12 @@ where
         self.pos = self.stack.pop().unwrap();
     }
 
+    /// Pop the last position pushed to the stack
+    /// and keep the current position
+    pub fn commit(&mut self) {
+        self.stack.pop();
+    }
+
     /// Update the top of the stack
     /// to be the current position
     pub fn update_save(&mut self) {
@@ -46,6 +159,20 @@ where
         self.data.get(self.pos).cloned()
     }
 
+    /// Position of the first element of the buffer
+    pub fn position(&self) -> Pos {
+        self.positions.get(self.pos).copied().unwrap_or(self.end)
+    }
+
+    /// Build an error expecting something else than
+    /// the first element of the buffer
+    pub fn error<E: Display>(&self, expected: E) -> ParseError {
+        match self.top() {
+            Some(x) => ParseError::new(self.position(), expected, x),
+            None => ParseError::new(self.end, expected, self.eof),
+        }
+    }
+
     /// Check if there are still elements to read
     /// in the buffer
     pub fn is_empty(&self) -> bool {
@@ -66,180 +193,322 @@ where
     /// Get the first element of the buffer
     /// and drops it.
     /// Returns `None` if the buffer is empty
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<T> {
         let x = self.top()?;
         self.pop()?;
         Some(x)
     }
 
+    /// Get the first element of the buffer and drops it.
+    /// Fails if the buffer is empty
+    pub fn expect_next<E: Display>(&mut self, expected: E) -> Result<T, ParseError> {
+        self.next().ok_or_else(|| self.error(expected))
+    }
+
     /// Compare a given element with the first
     /// element of the buffer and drops it if they
     /// are equals.
-    /// Returns `None` if the buffer is empty
-    pub fn expect(&mut self, x: T) -> Option<()> {
-        let y = self.next()?;
-        if x == y {
-            Some(())
-        } else {
-            None
-        }
+    pub fn expect(&mut self, x: T) -> Result<(), ParseError> {
+        self.expect_cond(|y| *y == x, &x).map(|_| ())
     }
 
     /// Expect the current element of the buffer to
     /// satisfies a condition and returns it.
-    pub fn expect_cond<P>(&mut self, cond: P) -> Option<T>
+    /// `expected` describes the condition in error messages
+    pub fn expect_cond<P, E>(&mut self, cond: P, expected: E) -> Result<T, ParseError>
     where
         P: FnOnce(&T) -> bool,
+        E: Display,
     {
-        let y = self.next()?;
-        if cond(&y) {
-            Some(y)
-        } else {
-            None
+        match self.top() {
+            Some(y) if cond(&y) => {
+                self.pop();
+                Ok(y)
+            }
+            _ => Err(self.error(expected)),
         }
     }
 
     /// Expect the current element of the buffer to
     /// be convertible.
-    /// The conversion function is expected to return `None` if
+    /// The conversion function is expected to fail if
     /// the element is not convertible
-    pub fn expect_convert<U>(&mut self, conv: fn(T) -> Option<U>) -> Option<U> {
-        conv(self.next()?)
+    pub fn expect_convert<U>(
+        &mut self,
+        conv: fn(T) -> Result<U, ParseError>,
+    ) -> Result<U, ParseError> {
+        conv(self.expect_next("an element")?)
     }
 
     /// Expect a non-empty sequence of
     /// elements satisfying a given condition.
     /// Elements are converted according to a given conversion
     /// function.
-    pub fn convert_while<U>(&mut self, pre: fn(&T) -> bool, conv: fn(T) -> U) -> Option<Vec<U>> {
-        let mut list = vec![conv(self.expect_cond(pre)?)];
-        self.save();
-        while let Some(l) = self.expect_cond(pre) {
+    pub fn convert_while<U, E: Display>(
+        &mut self,
+        pre: fn(&T) -> bool,
+        conv: fn(T) -> U,
+        expected: E,
+    ) -> Result<Vec<U>, ParseError> {
+        let mut list = vec![conv(self.expect_cond(pre, expected)?)];
+        while let Some(l) = self.top().filter(pre) {
+            self.pop();
             list.push(conv(l));
-            self.update_save();
         }
-        self.restore();
-        Some(list)
+        Ok(list)
     }
 
-    /// Expect a non-empty sequence of
-    /// elements satisfying a given condition.
-    /// The condition is given as a conversion function
-    /// returning `None` if the element violates the condition
-    pub fn convert_list<U>(&mut self, conv: fn(T) -> Option<U>) -> Option<Vec<U>> {
-        let mut list = vec![conv(self.next()?)?];
-        self.save();
-        while let Some(l) = self.expect_convert(conv) {
-            list.push(l);
-            self.update_save();
+    /// Convert all the remaining elements of the buffer,
+    /// which must not be empty.
+    /// The conversion function is expected to fail if
+    /// an element is not convertible
+    pub fn convert_list<U>(
+        &mut self,
+        conv: fn(T) -> Result<U, ParseError>,
+    ) -> Result<Vec<U>, ParseError> {
+        let mut list = vec![self.expect_convert(conv)?];
+        while !self.is_empty() {
+            list.push(self.expect_convert(conv)?);
         }
-        self.restore();
-        Some(list)
+        Ok(list)
     }
 
     /// Parse a non-empty list of elements according
-    /// to a parsing function
-    pub fn expect_list<U>(&mut self, parse: fn(&mut Buff<T>) -> Option<U>) -> Option<Vec<U>> {
+    /// to a parsing function, as long as it succeeds
+    pub fn expect_list<U>(
+        &mut self,
+        parse: fn(&mut Buff<T>) -> Result<U, ParseError>,
+    ) -> Result<Vec<U>, ParseError> {
         let mut list = vec![parse(self)?];
         self.save();
-        while let Some(l) = parse(self) {
+        while let Ok(l) = parse(self) {
             list.push(l);
             self.update_save();
         }
         self.restore();
-        Some(list)
+        Ok(list)
     }
 
-    pub fn expect_end(&self) -> Option<()> {
+    pub fn expect_end(&self) -> Result<(), ParseError> {
         if self.top().is_some() {
-            None
+            Err(self.error(self.eof))
         } else {
-            Some(())
+            Ok(())
         }
     }
 
-    pub fn expect_one_of(&mut self, alt: Vec<T>) -> Option<()> {
-        let y = self.next()?;
-        if alt.contains(&y) {
-            Some(())
-        } else {
-            None
-        }
+    pub fn expect_one_of(&mut self, alt: Vec<T>) -> Result<(), ParseError> {
+        let expected = alt
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.expect_cond(|y| alt.contains(y), format!("one of {}", expected))
+            .map(|_| ())
     }
 }
 
 impl Buff<char> {
+    /// Create a new buffer from a text,
+    /// locating each character by its line and column
+    pub fn from_text(s: &str) -> Self {
+        let (chars, end) = Pos::locate(s);
+        Buff::located(chars, end, "end of input")
+    }
+
     fn top_is_space(&self) -> bool {
         if let Some(c) = self.top() {
-            vec!['\t', '\n', ' '].contains(&c)
+            ['\t', '\n', '\r', ' '].contains(&c)
         } else {
             false
         }
     }
 
-    pub fn trim(&mut self) {
-        while !self.is_empty() && self.top_is_space() {
-            self.pos += 1;
+    /// Check if the buffer starts with a given text
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.data.get(self.pos + i) == Some(&c))
+    }
+
+    /// Skip a block comment `#| ... |#`.
+    /// Block comments can be nested.
+    /// Fails at the opening `#|` if the comment is not closed
+    fn skip_block_comment(&mut self) -> Result<(), ParseError> {
+        let (start, pos) = (self.position(), self.pos);
+        let mut depth = 0;
+        while !self.is_empty() {
+            if self.starts_with("#|") {
+                depth += 1;
+                self.pos += 2;
+            } else if self.starts_with("|#") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else {
+                self.pos += 1;
+            }
         }
+        self.pos = pos;
+        Err(ParseError::new(
+            start,
+            "a block comment closed by |#",
+            self.eof,
+        ))
     }
 
-    pub fn expect_u32(&mut self) -> Option<u32> {
-        self.trim();
+    /// Skip blanks, line comments `; ...` and block comments `#| ... |#`.
+    /// Fails on unterminated block comments
+    pub fn trim(&mut self) -> Result<(), ParseError> {
+        loop {
+            if self.top_is_space() {
+                self.pos += 1;
+            } else if self.top() == Some(';') {
+                while self.top().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else if self.starts_with("#|") {
+                self.skip_block_comment()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consume a token (after blanks and comments)
+    /// if the buffer starts with it
+    pub fn eat(&mut self, token: &str) -> bool {
+        if self.trim().is_ok() && self.starts_with(token) {
+            self.pos += token.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect_u32(&mut self) -> Result<u32, ParseError> {
+        self.trim()?;
+        let start = self.position();
         let mut num = self.expect_digit()?.to_digit(10).unwrap();
         while let Some(c) = self.top() {
-            if c.is_digit(10) {
+            if c.is_ascii_digit() {
                 self.pop();
-                num += 10 * num + c.to_digit(10).unwrap();
+                num = num
+                    .checked_mul(10)
+                    .and_then(|num| num.checked_add(c.to_digit(10).unwrap()))
+                    .ok_or_else(|| ParseError::new(start, "a 32-bit number", "a larger number"))?;
             } else {
                 break;
             }
         }
-        Some(num)
+        Ok(num)
     }
 
-    pub fn expect_alpha(&mut self) -> Option<char> {
-        let c = self.next()?;
-        if c.is_ascii_alphabetic() {
-            Some(c)
-        } else {
-            None
-        }
+    pub fn expect_alpha(&mut self) -> Result<char, ParseError> {
+        self.expect_cond(char::is_ascii_alphabetic, "a letter")
     }
 
-    pub fn expect_digit(&mut self) -> Option<char> {
-        let c = self.next()?;
-        if c.is_digit(10) {
-            Some(c)
-        } else {
-            None
-        }
+    pub fn expect_digit(&mut self) -> Result<char, ParseError> {
+        self.expect_cond(char::is_ascii_digit, "a digit")
     }
 
-    pub fn expect_symb(&mut self) -> Option<String> {
-        self.trim();
+    /// Expect a symbol: either a letter or `_` followed by letters,
+    /// digits and the characters `_`, `-`, `.` and `'`,
+    /// or an operator made of the characters `+`, `-`, `*`, `/`, `<`, `>` and `=`
+    pub fn expect_symb(&mut self) -> Result<String, ParseError> {
+        const OPERATORS: [char; 7] = ['+', '-', '*', '/', '<', '>', '='];
+        self.trim()?;
         let mut symb = String::new();
-        symb.push(self.expect_alpha()?);
+        let first = self.expect_cond(
+            |c| c.is_ascii_alphabetic() || *c == '_' || OPERATORS.contains(c),
+            "a symbol",
+        )?;
+        symb.push(first);
+        let is_operator = OPERATORS.contains(&first);
         while let Some(c) = self.top() {
-            if c.is_alphanumeric() {
+            let accepted = if is_operator {
+                OPERATORS.contains(&c)
+            } else {
+                c.is_alphanumeric() || ['_', '-', '.', '\''].contains(&c)
+            };
+            if accepted {
                 self.pop();
                 symb.push(c);
             } else {
                 break;
             }
         }
-        Some(symb)
+        Ok(symb)
     }
 
-    pub fn expect_blank(&mut self) -> Option<()> {
-        self.expect_one_of(vec![' ', '\t', '\n'])
+    pub fn expect_blank(&mut self) -> Result<(), ParseError> {
+        self.expect_one_of(vec![' ', '\t', '\n', '\r'])
     }
 
-    pub fn expect_token(&mut self, tok: String) -> Option<()> {
+    pub fn expect_token(&mut self, tok: String) -> Result<(), ParseError> {
+        self.trim()?;
+        let pos = self.position();
         let symb = self.expect_symb()?;
         if symb == tok {
-            Some(())
+            Ok(())
         } else {
-            None
+            Err(ParseError::new(pos, tok, symb))
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_1() {
+        let mut buff = Buff::from_text("12 345\n  x");
+        assert_eq!(buff.expect_u32(), Ok(12));
+        assert_eq!(buff.expect_u32(), Ok(345));
+        assert_eq!(
+            buff.expect_u32(),
+            Err(ParseError::new(Pos { line: 2, col: 3 }, "a digit", 'x'))
+        );
+    }
+
+    #[test]
+    fn test_2() {
+        let mut buff = Buff::from_text("ab");
+        assert!(buff.expect_token("ab".to_string()).is_ok());
+        assert_eq!(
+            buff.expect('c'),
+            Err(ParseError::new(
+                Pos { line: 1, col: 3 },
+                'c',
+                "end of input"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_3() {
+        let mut buff =
+            Buff::from_text("; comment\r\n#| block #| nested |# |#\r\n  req_sent msg.ack x'");
+        assert_eq!(buff.expect_symb(), Ok("req_sent".to_string()));
+        assert_eq!(buff.expect_symb(), Ok("msg.ack".to_string()));
+        assert_eq!(buff.expect_symb(), Ok("x'".to_string()));
+        assert!(buff.expect_end().is_ok());
+    }
+
+    #[test]
+    fn test_4() {
+        let mut buff = Buff::from_text("x\n  #| open #| nested |#\n y");
+        assert_eq!(buff.expect_symb(), Ok("x".to_string()));
+        let err = ParseError::new(
+            Pos { line: 2, col: 3 },
+            "a block comment closed by |#",
+            "end of input",
+        );
+        assert_eq!(buff.trim(), Err(err.clone()));
+        assert_eq!(buff.expect_symb(), Err(err));
+    }
+}
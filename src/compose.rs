@@ -0,0 +1,181 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{dense::Transitions, ts::Ts};
+
+/// Cartesian product of a list of choices
+fn product(choices: Vec<Vec<u32>>) -> Vec<Vec<u32>> {
+    choices.into_iter().fold(vec![vec![]], |tuples, choice| {
+        tuples
+            .iter()
+            .flat_map(|tuple| {
+                choice.iter().map(move |x| {
+                    let mut tuple = tuple.clone();
+                    tuple.push(*x);
+                    tuple
+                })
+            })
+            .collect()
+    })
+}
+
+/// Number of a tuple of states, numbering it if it is new
+fn intern(
+    ids: &mut HashMap<Vec<u32>, u32>,
+    queue: &mut VecDeque<Vec<u32>>,
+    tuple: Vec<u32>,
+) -> u32 {
+    if let Some(id) = ids.get(&tuple) {
+        return *id;
+    }
+    let id = ids.len() as u32 + 1;
+    ids.insert(tuple.clone(), id);
+    queue.push_back(tuple);
+    id
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Parallel composition of transition systems.
+    ///
+    /// The actions of `sync` are performed simultaneously by all the
+    /// components having them in their alphabet (the actions labelling their
+    /// transitions), the other actions are interleaved.
+    /// The states of the composition are the reachable tuples of states of the
+    /// components, numbered from 1 and labelled by the union of their labels.
+    /// The specification of the composition gathers the ones of the components.
+    pub fn compose(components: &[&Ts<A, P>], sync: &[A]) -> Ts<A, P> {
+        let alphabets = components
+            .iter()
+            .map(|ts| ts.actions().into_iter().collect::<HashSet<A>>())
+            .collect::<Vec<_>>();
+        let mut ids = HashMap::new();
+        let mut queue = VecDeque::new();
+        let initials = components
+            .iter()
+            .map(|ts| ts.initial.iter().copied().collect())
+            .collect();
+        let initial = product(initials)
+            .into_iter()
+            .map(|tuple| intern(&mut ids, &mut queue, tuple))
+            .collect();
+        let mut labels = HashMap::new();
+        let mut names = HashMap::new();
+        let mut transitions = vec![];
+        while let Some(tuple) = queue.pop_front() {
+            let s = ids[&tuple];
+            let label = components
+                .iter()
+                .zip(tuple.iter())
+                .flat_map(|(ts, x)| ts.label(x))
+                .collect::<HashSet<P>>();
+            if !label.is_empty() {
+                labels.insert(s, label);
+            }
+            let name = components
+                .iter()
+                .zip(tuple.iter())
+                .map(|(ts, x)| ts.state_name(*x))
+                .collect::<Vec<_>>();
+            names.insert(s, format!("({})", name.join(", ")));
+            let mut moves = vec![];
+            // Interleaved moves of a single component
+            for (i, ts) in components.iter().enumerate() {
+                for (a, t) in ts.transitions.post(&tuple[i]) {
+                    if sync.contains(a) {
+                        continue;
+                    }
+                    let mut next = tuple.clone();
+                    next[i] = *t;
+                    moves.push((a.clone(), next));
+                }
+            }
+            // Synchronised moves of all the components knowing the action
+            for a in sync {
+                if !alphabets.iter().any(|alphabet| alphabet.contains(a)) {
+                    continue;
+                }
+                let choices = components
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ts)| {
+                        if alphabets[i].contains(a) {
+                            ts.succ(&tuple[i], a).copied().collect()
+                        } else {
+                            vec![tuple[i]]
+                        }
+                    })
+                    .collect();
+                for next in product(choices) {
+                    moves.push((a.clone(), next));
+                }
+            }
+            for (a, next) in moves {
+                let t = intern(&mut ids, &mut queue, next);
+                transitions.push((s, a, t));
+            }
+        }
+        let states = ids.into_values().collect();
+        let transitions = Transitions::new(&states, transitions);
+        Ts {
+            labels: transitions.to_label_bits(labels),
+            transitions,
+            states,
+            initial,
+            spec: components
+                .iter()
+                .flat_map(|ts| ts.spec.iter().cloned())
+                .collect(),
+            fairness: components
+                .iter()
+                .flat_map(|ts| ts.fairness.iter().cloned())
+                .collect(),
+            names,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ctl::Ctl, spec::Spec, ts::Ts};
+
+    fn component(a: char, b: char, p: char) -> Ts<char, char> {
+        Ts::new::<Spec<char, char>>(
+            vec![1, 2],
+            vec![1],
+            vec![(2, vec![p])],
+            vec![(1, vec![(a, 2)]), (2, vec![(b, 1)])],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_1() {
+        let p = component('a', 's', 'P');
+        let q = component('b', 's', 'Q');
+        let ts = Ts::compose(&[&p, &q], &['s']);
+        assert_eq!(ts.states.len(), 4);
+        assert_eq!(ts.transitions.iter().count(), 5);
+        // The synchronised action is only possible when both components can take it
+        let top = Box::new(Ctl::Neg(Box::new(Ctl::Lit('X'))));
+        let both = Ctl::And(Box::new(Ctl::Lit('P')), Box::new(Ctl::Lit('Q')));
+        assert_eq!(ts.sat_ctl(&Ctl::Ex(Some('s'), top)), ts.sat_ctl(&both));
+        assert_eq!(ts.sat_ctl(&both).len(), 1);
+    }
+
+    #[test]
+    fn test_2() {
+        let p = component('a', 's', 'P');
+        let q = component('b', 's', 'Q');
+        let ts = Ts::compose(&[&p, &q], &[]);
+        assert_eq!(ts.actions().len(), 3);
+        assert_eq!(ts.post(&1).count(), 2);
+        assert_eq!(ts.state_name(1), "(1, 1)");
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt::Display;
+
+use crate::{
+    act::Act,
+    buff::{Buff, ParseError},
+    mu::Mu,
+    sexpr::Sexpr,
+};
+
+/// Formulas of the Computation Tree Logic.
+///
+/// The next-step operators can optionally be restricted to a single action,
+/// the other temporal operators range over all the transitions of the system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Ctl<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    Lit(P),
+    Neg(Box<Ctl<A, P>>),
+    And(Box<Ctl<A, P>>, Box<Ctl<A, P>>),
+    Or(Box<Ctl<A, P>>, Box<Ctl<A, P>>),
+    Ex(Option<A>, Box<Ctl<A, P>>),
+    Ax(Option<A>, Box<Ctl<A, P>>),
+    Ef(Box<Ctl<A, P>>),
+    Af(Box<Ctl<A, P>>),
+    Eg(Box<Ctl<A, P>>),
+    Ag(Box<Ctl<A, P>>),
+    Eu(Box<Ctl<A, P>>, Box<Ctl<A, P>>),
+    Au(Box<Ctl<A, P>>, Box<Ctl<A, P>>),
+}
+
+/// Generator of fresh fixpoint variables for the translation into μ-calculus
+pub(crate) struct Fresh(pub(crate) usize);
+
+impl Fresh {
+    pub(crate) fn next(&mut self) -> String {
+        self.0 += 1;
+        format!("X{}", self.0)
+    }
+}
+
+impl<A, P> Ctl<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+    P: Clone,
+{
+    /// Check if the formula uses the next-step operators `EX` or `AX`
+    pub fn has_next(&self) -> bool {
+        match self {
+            Ctl::Lit(_) => false,
+            Ctl::Ex(_, _) | Ctl::Ax(_, _) => true,
+            Ctl::Neg(a) | Ctl::Ef(a) | Ctl::Af(a) | Ctl::Eg(a) | Ctl::Ag(a) => a.has_next(),
+            Ctl::And(a, b) | Ctl::Or(a, b) | Ctl::Eu(a, b) | Ctl::Au(a, b) => {
+                a.has_next() || b.has_next()
+            }
+        }
+    }
+
+    fn mu_true(fresh: &mut Fresh) -> Mu<A, P> {
+        let x = fresh.next();
+        Mu::Gfp(x.clone(), Box::new(Mu::Var(x)))
+    }
+
+    fn mu_false(fresh: &mut Fresh) -> Mu<A, P> {
+        let x = fresh.next();
+        Mu::Lfp(x.clone(), Box::new(Mu::Var(x)))
+    }
+
+    /// The actions a next-step operator ranges over
+    fn next_actions(act: &Option<A>) -> Act<A> {
+        match act {
+            Some(a) => Act::One(a.clone()),
+            None => Act::True,
+        }
+    }
+
+    /// Some successor satisfies `mu`
+    fn mu_ex(mu: Mu<A, P>) -> Mu<A, P> {
+        Mu::Ex(Act::True, Box::new(mu))
+    }
+
+    /// All successors satisfy `mu`
+    fn mu_ax(mu: Mu<A, P>) -> Mu<A, P> {
+        Mu::All(Act::True, Box::new(mu))
+    }
+
+    fn translate(&self, fresh: &mut Fresh) -> Mu<A, P> {
+        match self {
+            Ctl::Lit(p) => Mu::Lit(p.clone()),
+            Ctl::Neg(a) => Mu::Neg(Box::new(a.translate(fresh))),
+            Ctl::And(a, b) => Mu::And(Box::new(a.translate(fresh)), Box::new(b.translate(fresh))),
+            Ctl::Or(a, b) => Mu::Or(Box::new(a.translate(fresh)), Box::new(b.translate(fresh))),
+            Ctl::Ex(act, a) => {
+                let a = a.translate(fresh);
+                Mu::Ex(Self::next_actions(act), Box::new(a))
+            }
+            Ctl::Ax(act, a) => {
+                let a = a.translate(fresh);
+                Mu::All(Self::next_actions(act), Box::new(a))
+            }
+            Ctl::Ef(a) => {
+                let a = a.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ex(Mu::Var(x.clone()));
+                Mu::Lfp(x, Box::new(Mu::Or(Box::new(a), Box::new(step))))
+            }
+            Ctl::Af(a) => {
+                let a = a.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ax(Mu::Var(x.clone()));
+                let tt = Self::mu_true(fresh);
+                let live = Self::mu_ex(tt);
+                let step = Mu::And(Box::new(step), Box::new(live));
+                Mu::Lfp(x, Box::new(Mu::Or(Box::new(a), Box::new(step))))
+            }
+            Ctl::Eg(a) => {
+                let a = a.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ex(Mu::Var(x.clone()));
+                let ff = Self::mu_false(fresh);
+                let dead = Self::mu_ax(ff);
+                let step = Mu::Or(Box::new(step), Box::new(dead));
+                Mu::Gfp(x, Box::new(Mu::And(Box::new(a), Box::new(step))))
+            }
+            Ctl::Ag(a) => {
+                let a = a.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ax(Mu::Var(x.clone()));
+                Mu::Gfp(x, Box::new(Mu::And(Box::new(a), Box::new(step))))
+            }
+            Ctl::Eu(a, b) => {
+                let a = a.translate(fresh);
+                let b = b.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ex(Mu::Var(x.clone()));
+                let step = Mu::And(Box::new(a), Box::new(step));
+                Mu::Lfp(x, Box::new(Mu::Or(Box::new(b), Box::new(step))))
+            }
+            Ctl::Au(a, b) => {
+                let a = a.translate(fresh);
+                let b = b.translate(fresh);
+                let x = fresh.next();
+                let step = Self::mu_ax(Mu::Var(x.clone()));
+                let tt = Self::mu_true(fresh);
+                let live = Self::mu_ex(tt);
+                let step = Mu::And(
+                    Box::new(a),
+                    Box::new(Mu::And(Box::new(step), Box::new(live))),
+                );
+                Mu::Lfp(x, Box::new(Mu::Or(Box::new(b), Box::new(step))))
+            }
+        }
+    }
+
+    /// Translate a CTL formula into an equivalent μ-calculus formula.
+    ///
+    /// Path quantifiers range over maximal paths, so a path may end in a
+    /// deadlock. The operators ranging over all transitions use modalities
+    /// over any action.
+    pub fn to_mu(&self) -> Mu<A, P> {
+        self.translate(&mut Fresh(0))
+    }
+}
+
+impl Ctl<String, String> {
+    fn parse_binop(buff: &mut Buff<Sexpr>, is_or: bool) -> Result<Self, ParseError> {
+        let args = buff.convert_list(Self::from_sexpr)?;
+        let res = if is_or {
+            args.into_iter()
+                .reduce(|lhs, rhs| Ctl::Or(Box::new(lhs), Box::new(rhs)))
+        } else {
+            args.into_iter()
+                .reduce(|lhs, rhs| Ctl::And(Box::new(lhs), Box::new(rhs)))
+        };
+        Ok(res.unwrap())
+    }
+
+    fn parse_unary(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let ctl = buff.expect_convert(Self::from_sexpr)?;
+        buff.expect_end()?;
+        Ok(ctl)
+    }
+
+    fn parse_next(buff: &mut Buff<Sexpr>, is_ex: bool) -> Result<Self, ParseError> {
+        let first = buff.expect_next("a CTL formula")?;
+        let (action, ctl) = if buff.is_empty() {
+            (None, Self::from_sexpr(first)?)
+        } else {
+            let action = first
+                .clone()
+                .get_singleton_opt()
+                .and_then(Sexpr::get_symb_opt)
+                .ok_or_else(|| first.error("a symbol between parentheses"))?;
+            (Some(action), Self::parse_unary(buff)?)
+        };
+        if is_ex {
+            Ok(Ctl::Ex(action, Box::new(ctl)))
+        } else {
+            Ok(Ctl::Ax(action, Box::new(ctl)))
+        }
+    }
+
+    fn parse_until(buff: &mut Buff<Sexpr>, is_eu: bool) -> Result<Self, ParseError> {
+        let lhs = Box::new(buff.expect_convert(Self::from_sexpr)?);
+        let rhs = Box::new(buff.expect_convert(Self::from_sexpr)?);
+        buff.expect_end()?;
+        if is_eu {
+            Ok(Ctl::Eu(lhs, rhs))
+        } else {
+            Ok(Ctl::Au(lhs, rhs))
+        }
+    }
+
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        match sexpr {
+            Sexpr::Sym(s, _) => Ok(Ctl::Lit(s)),
+            Sexpr::Num(_, _) | Sexpr::Str(_, _) => Err(sexpr.error("a CTL formula")),
+            Sexpr::List(_, _) => {
+                let mut buff = sexpr.into_buff("a CTL formula")?;
+                let pos = buff.position();
+                let op = buff.expect_symb()?;
+                match op.as_str() {
+                    "ex" | "ax" => Self::parse_next(&mut buff, op == "ex"),
+                    "eu" | "au" => Self::parse_until(&mut buff, op == "eu"),
+                    "ef" => Ok(Ctl::Ef(Box::new(Self::parse_unary(&mut buff)?))),
+                    "af" => Ok(Ctl::Af(Box::new(Self::parse_unary(&mut buff)?))),
+                    "eg" => Ok(Ctl::Eg(Box::new(Self::parse_unary(&mut buff)?))),
+                    "ag" => Ok(Ctl::Ag(Box::new(Self::parse_unary(&mut buff)?))),
+                    "not" => Ok(Ctl::Neg(Box::new(Self::parse_unary(&mut buff)?))),
+                    "or" | "and" => Self::parse_binop(&mut buff, op == "or"),
+                    _ => Err(ParseError::new(pos, "a CTL operator", op)),
+                }
+            }
+        }
+    }
+}
+
+impl<A, P> Display for Ctl<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ctl::Lit(p) => write!(f, "{}", p),
+            Ctl::Neg(a) => write!(f, "¬{}", a),
+            Ctl::And(a, b) => write!(f, "({} ∧ {})", a, b),
+            Ctl::Or(a, b) => write!(f, "({} ∨ {})", a, b),
+            Ctl::Ex(Some(act), a) => write!(f, "EX[{}] {}", act, a),
+            Ctl::Ex(None, a) => write!(f, "EX {}", a),
+            Ctl::Ax(Some(act), a) => write!(f, "AX[{}] {}", act, a),
+            Ctl::Ax(None, a) => write!(f, "AX {}", a),
+            Ctl::Ef(a) => write!(f, "EF {}", a),
+            Ctl::Af(a) => write!(f, "AF {}", a),
+            Ctl::Eg(a) => write!(f, "EG {}", a),
+            Ctl::Ag(a) => write!(f, "AG {}", a),
+            Ctl::Eu(a, b) => write!(f, "E[{} U {}]", a, b),
+            Ctl::Au(a, b) => write!(f, "A[{} U {}]", a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ctl::*;
+    use super::*;
+
+    fn parse(s: &str) -> Option<Ctl<String, String>> {
+        Ctl::from_sexpr(s.parse::<Sexpr>().ok()?).ok()
+    }
+
+    fn lit(p: &str) -> Box<Ctl<String, String>> {
+        Box::new(Lit(p.to_string()))
+    }
+
+    #[test]
+    fn test_1() {
+        assert_eq!(parse("(ag P)"), Some(Ag(lit("P"))))
+    }
+
+    #[test]
+    fn test_2() {
+        assert_eq!(parse("(eu P Q)"), Some(Eu(lit("P"), lit("Q"))))
+    }
+
+    #[test]
+    fn test_3() {
+        assert_eq!(
+            parse("(ex (a) P)"),
+            Some(Ex(Some("a".to_string()), lit("P")))
+        )
+    }
+
+    #[test]
+    fn test_4() {
+        assert_eq!(
+            parse("(ax (not P))"),
+            Some(Ax(None, Box::new(Neg(lit("P")))))
+        )
+    }
+
+    #[test]
+    fn test_5() {
+        assert_eq!(
+            parse("(af (and P Q R))"),
+            Some(Af(Box::new(And(
+                Box::new(And(lit("P"), lit("Q"))),
+                lit("R")
+            ))))
+        )
+    }
+
+    #[test]
+    fn test_6() {
+        assert!(parse("(eu P)").is_none());
+        assert!(parse("(lfp (x) x)").is_none());
+    }
+
+    #[test]
+    fn test_7() {
+        let ctl: Ctl<char, char> = Ctl::Ex(None, Box::new(Ctl::Lit('P')));
+        assert_eq!(ctl.to_mu(), Mu::Ex(Act::True, Box::new(Mu::Lit('P'))))
+    }
+}
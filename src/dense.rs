@@ -0,0 +1,281 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{act::Act, bits::BitSet};
+
+/// Adjacency lists stored contiguously: the edges leaving node `i`
+/// are `edges[offsets[i]..offsets[i + 1]]`, as pairs of an action
+/// index and a target node
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Csr {
+    offsets: Vec<usize>,
+    edges: Vec<(u32, u32)>,
+}
+
+impl Csr {
+    /// Build the table of `n` nodes from a sorted list of edges `(source, action, target)`
+    fn new(n: usize, triples: &[(u32, u32, u32)]) -> Self {
+        let mut offsets = vec![0; n + 1];
+        for (i, _, _) in triples {
+            offsets[*i as usize + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut next = offsets.clone();
+        let mut edges = vec![(0, 0); triples.len()];
+        for (i, a, j) in triples {
+            edges[next[*i as usize]] = (*a, *j);
+            next[*i as usize] += 1;
+        }
+        Csr { offsets, edges }
+    }
+
+    fn edges(&self, i: usize) -> &[(u32, u32)] {
+        &self.edges[self.offsets[i]..self.offsets[i + 1]]
+    }
+}
+
+/// The transition relation of a system. States are numbered densely
+/// from 0, in increasing order, so that sets of states can be stored
+/// as bit vectors, and the successors and predecessors of every state
+/// are stored in CSR tables.
+#[derive(Clone, Debug)]
+pub(crate) struct Transitions<A> {
+    /// The state numbered by each index
+    states: Vec<u32>,
+    index: HashMap<u32, usize>,
+    actions: Vec<A>,
+    action_index: HashMap<A, u32>,
+    post: Csr,
+    pre: Csr,
+}
+
+impl<A> Transitions<A>
+where
+    A: Eq + Hash + Clone,
+{
+    /// The relation made of the edges `(source, action, target)`
+    /// between the given states.
+    /// Edges with an end outside of `states` are ignored
+    pub(crate) fn new<I>(states: &HashSet<u32>, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (u32, A, u32)>,
+    {
+        let mut numbers = states.iter().copied().collect::<Vec<_>>();
+        numbers.sort();
+        let index = numbers
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (*x, i))
+            .collect::<HashMap<_, _>>();
+        let mut actions = vec![];
+        let mut action_index = HashMap::new();
+        let mut forward = edges
+            .into_iter()
+            .filter(|(x, _, y)| index.contains_key(x) && index.contains_key(y))
+            .map(|(x, a, y)| {
+                let k = *action_index.entry(a).or_insert_with_key(|a| {
+                    actions.push(a.clone());
+                    actions.len() as u32 - 1
+                });
+                (index[&x] as u32, k, index[&y] as u32)
+            })
+            .collect::<Vec<_>>();
+        forward.sort();
+        forward.dedup();
+        let mut backward = forward
+            .iter()
+            .map(|(i, a, j)| (*j, *a, *i))
+            .collect::<Vec<_>>();
+        backward.sort();
+        Transitions {
+            post: Csr::new(numbers.len(), &forward),
+            pre: Csr::new(numbers.len(), &backward),
+            states: numbers,
+            index,
+            actions,
+            action_index,
+        }
+    }
+}
+
+impl<A> Transitions<A>
+where
+    A: Eq + Hash,
+{
+    pub(crate) fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// The index of a state, if it is a state of the relation
+    pub(crate) fn index(&self, x: &u32) -> Option<usize> {
+        self.index.get(x).copied()
+    }
+
+    /// The actions labelling the edges
+    pub(crate) fn actions(&self) -> &[A] {
+        &self.actions
+    }
+
+    fn adjacent<'a>(&'a self, table: &'a Csr, x: &u32) -> &'a [(u32, u32)] {
+        self.index.get(x).map_or(&[][..], |i| table.edges(*i))
+    }
+
+    /// The edges leaving a state, as pairs of an action and a target
+    pub(crate) fn post<'a>(&'a self, x: &u32) -> impl Iterator<Item = (&'a A, &'a u32)> + 'a {
+        self.adjacent(&self.post, x)
+            .iter()
+            .map(|(a, j)| (&self.actions[*a as usize], &self.states[*j as usize]))
+    }
+
+    /// The edges entering a state, as pairs of an action and a source
+    pub(crate) fn pre<'a>(&'a self, x: &u32) -> impl Iterator<Item = (&'a A, &'a u32)> + 'a {
+        self.adjacent(&self.pre, x)
+            .iter()
+            .map(|(a, i)| (&self.actions[*a as usize], &self.states[*i as usize]))
+    }
+
+    /// The successors of a state through a given action
+    pub(crate) fn succ<'a>(&'a self, x: &u32, act: &A) -> impl Iterator<Item = &'a u32> + 'a {
+        let k = self.action_index.get(act).copied();
+        self.adjacent(&self.post, x)
+            .iter()
+            .filter(move |(a, _)| Some(*a) == k)
+            .map(|(_, j)| &self.states[*j as usize])
+    }
+
+    /// All the edges, as triples `(source, action, target)`
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&u32, &A, &u32)> + '_ {
+        self.states.iter().enumerate().flat_map(move |(i, x)| {
+            self.post
+                .edges(i)
+                .iter()
+                .map(move |(a, j)| (x, &self.actions[*a as usize], &self.states[*j as usize]))
+        })
+    }
+
+    /// The actions matched by an action formula, by index
+    pub(crate) fn matching(&self, act: &Act<A>) -> Vec<bool> {
+        self.actions.iter().map(|a| act.matches(a)).collect()
+    }
+
+    /// The set of indices standing for a set of states
+    pub(crate) fn to_bits<'a, I>(&self, states: I) -> BitSet
+    where
+        I: IntoIterator<Item = &'a u32>,
+    {
+        let mut set = BitSet::new(self.len());
+        for x in states {
+            if let Some(i) = self.index.get(x) {
+                set.insert(*i);
+            }
+        }
+        set
+    }
+
+    /// The sets of indices of the states labelled by each proposition
+    pub(crate) fn to_label_bits<P, L>(&self, labels: L) -> HashMap<P, BitSet>
+    where
+        P: Eq + Hash,
+        L: IntoIterator<Item = (u32, HashSet<P>)>,
+    {
+        let mut bits = HashMap::<P, BitSet>::new();
+        for (x, label) in labels {
+            if let Some(i) = self.index(&x) {
+                for p in label {
+                    bits.entry(p)
+                        .or_insert_with(|| BitSet::new(self.len()))
+                        .insert(i);
+                }
+            }
+        }
+        bits
+    }
+
+    /// The set of states standing for a set of indices
+    pub(crate) fn to_states(&self, set: &BitSet) -> HashSet<u32> {
+        set.iter().map(|i| self.states[i]).collect()
+    }
+
+    /// The states with an edge through a matching action to a state of `set`,
+    /// following the edges backwards if `converse`
+    pub(crate) fn ex(&self, matching: &[bool], set: &BitSet, converse: bool) -> BitSet {
+        let table = if converse { &self.post } else { &self.pre };
+        let mut res = BitSet::new(self.len());
+        for j in set.iter() {
+            for (a, i) in table.edges(j) {
+                if matching[*a as usize] {
+                    res.insert(*i as usize);
+                }
+            }
+        }
+        res
+    }
+
+    /// The states all of whose edges through a matching action lead to `set`,
+    /// following the edges backwards if `converse`
+    pub(crate) fn all(&self, matching: &[bool], set: &BitSet, converse: bool) -> BitSet {
+        self.ex(matching, &set.clone().complement(), converse)
+            .complement()
+    }
+}
+
+/// Relations are equal when they have the same states and edges,
+/// whatever the numbering of their actions
+impl<A> PartialEq for Transitions<A>
+where
+    A: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.states == other.states
+            && self.iter().collect::<HashSet<_>>() == other.iter().collect::<HashSet<_>>()
+    }
+}
+
+impl<A> Eq for Transitions<A> where A: Eq + Hash {}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{act::Act, dense::Transitions};
+
+    #[test]
+    fn test_1() {
+        let states = HashSet::from([10, 20, 30]);
+        let edges = [
+            (10, 'a', 20),
+            (10, 'a', 30),
+            (20, 'b', 30),
+            (10, 'a', 20),
+            (30, 'a', 40),
+        ];
+        let transitions = Transitions::new(&states, edges);
+        assert_eq!(transitions.states, vec![10, 20, 30]);
+        assert_eq!(transitions.iter().count(), 3);
+        let p = transitions.to_bits(&[30]);
+        let a = transitions.matching(&Act::One('a'));
+        assert_eq!(
+            transitions.to_states(&transitions.ex(&a, &p, false)),
+            HashSet::from([10])
+        );
+        assert_eq!(
+            transitions.to_states(&transitions.ex(&a, &p, true)),
+            HashSet::new()
+        );
+        let all = transitions.matching(&Act::True);
+        assert_eq!(
+            transitions.to_states(&transitions.all(&all, &p, false)),
+            HashSet::from([20, 30])
+        );
+        let succ = transitions.succ(&10, &'a').copied().collect::<Vec<_>>();
+        assert_eq!(succ, vec![20, 30]);
+        assert_eq!(transitions.pre(&30).count(), 2);
+        assert_eq!(transitions.post(&30).count(), 0);
+        let reversed = Transitions::new(&states, edges.into_iter().rev());
+        assert_eq!(transitions, reversed);
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{explain::Trace, spec::Spec, ts::Ts};
+
+/// Options of the export of a transition system to GraphViz DOT
+#[derive(Clone, Debug)]
+pub struct DotOptions<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    /// Write the propositions labelling each state
+    pub labels: bool,
+    /// Fill the states satisfying this specification in green, and the others in red
+    pub spec: Option<Spec<A, P>>,
+    /// Draw this path in bold
+    pub trace: Option<Trace<A>>,
+    /// Gather the states having the same (non-empty) label in clusters
+    pub clusters: bool,
+}
+
+impl<A, P> Default for DotOptions<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn default() -> Self {
+        DotOptions {
+            labels: true,
+            spec: None,
+            trace: None,
+            clusters: false,
+        }
+    }
+}
+
+/// Escape the double quotes of a DOT string
+fn escape(s: &str) -> String {
+    s.replace('"', "\\\"")
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// The sorted propositions labelling a state, as text
+    fn label_text(&self, x: &u32) -> Vec<String> {
+        let mut label = self
+            .label(x)
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        label.sort();
+        label
+    }
+
+    /// Export the system to GraphViz DOT.
+    ///
+    /// Every state is declared, initial states being drawn with a double circle.
+    pub fn to_dot(&self, options: &DotOptions<A, P>) -> String {
+        let sat = options.spec.as_ref().map(|spec| self.sat_spec(spec));
+        let (path, steps) = match &options.trace {
+            Some(trace) => {
+                let states = trace.states();
+                let steps = states
+                    .iter()
+                    .zip(trace.steps.iter())
+                    .map(|(x, (a, y))| (*x, a.to_string(), *y))
+                    .collect::<HashSet<_>>();
+                (states.into_iter().collect(), steps)
+            }
+            None => (HashSet::new(), HashSet::new()),
+        };
+        let mut states = self.states.iter().copied().collect::<Vec<_>>();
+        states.sort();
+        let node = |x: &u32| {
+            let mut attributes = vec![];
+            if self.initial.contains(x) {
+                attributes.push("shape=doublecircle".to_string());
+            }
+            let mut label = escape(&self.state_name(*x));
+            if options.labels {
+                label = format!("{}\\n{{{}}}", label, escape(&self.label_text(x).join(", ")));
+            }
+            attributes.push(format!("label=\"{}\"", label));
+            if let Some(sat) = &sat {
+                let color = if sat.contains(x) {
+                    "palegreen"
+                } else {
+                    "lightpink"
+                };
+                attributes.push(format!("style=filled, fillcolor={}", color));
+            }
+            if path.contains(x) {
+                attributes.push("penwidth=2".to_string());
+            }
+            format!("{} [{}]", x, attributes.join(", "))
+        };
+        let mut lines = vec!["digraph {".to_string(), "  node [shape=circle]".to_string()];
+        if options.clusters {
+            let mut clusters = BTreeMap::<Vec<String>, Vec<u32>>::new();
+            for x in states.iter() {
+                clusters.entry(self.label_text(x)).or_default().push(*x);
+            }
+            for (i, (label, members)) in clusters.iter().enumerate() {
+                if label.is_empty() {
+                    lines.extend(members.iter().map(|x| format!("  {}", node(x))));
+                    continue;
+                }
+                lines.push(format!("  subgraph cluster_{} {{", i));
+                lines.push(format!("    label=\"{{{}}}\"", escape(&label.join(", "))));
+                lines.extend(members.iter().map(|x| format!("    {}", node(x))));
+                lines.push("  }".to_string());
+            }
+        } else {
+            lines.extend(states.iter().map(|x| format!("  {}", node(x))));
+        }
+        for x in states.iter() {
+            let mut post = self
+                .transitions
+                .post(x)
+                .map(|(a, y)| (a.to_string(), *y))
+                .collect::<Vec<_>>();
+            post.sort();
+            for (a, y) in post {
+                let bold = match steps.contains(&(*x, a.clone(), y)) {
+                    true => ", color=red, penwidth=2",
+                    false => "",
+                };
+                lines.push(format!(
+                    "  {} -> {} [label=\" {}\"{}];",
+                    x,
+                    y,
+                    escape(&a),
+                    bold
+                ));
+            }
+        }
+        lines.push("}".to_string());
+        lines.join("\n") + "\n"
+    }
+}
+
+impl<A, P> Display for Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_dot(&DotOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{act::Act, dot::DotOptions, explain::Trace, mu::Mu, spec::Spec, ts::Ts};
+
+    fn ts() -> Ts<char, char> {
+        Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3],
+            vec![1],
+            vec![(2, vec!['P']), (3, vec!['P'])],
+            vec![(1, vec![('a', 2), ('b', 3)]), (2, vec![('a', 3)])],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_1() {
+        let dot = ts().to_string();
+        assert!(dot.contains("  1 [shape=doublecircle, label=\"1\\n{}\"]"));
+        assert!(dot.contains("  3 [label=\"3\\n{P}\"]"));
+        assert!(dot.contains("  1 -> 3 [label=\" b\"];"));
+    }
+
+    #[test]
+    fn test_2() {
+        let options = DotOptions {
+            labels: false,
+            spec: Some(Spec::Mu(Mu::Ex(Act::One('a'), Box::new(Mu::Lit('P'))))),
+            trace: Some(Trace {
+                start: 1,
+                steps: vec![('a', 2)],
+                lasso: None,
+            }),
+            clusters: true,
+        };
+        let dot = ts().to_dot(&options);
+        assert!(dot.contains("subgraph cluster_1 {\n    label=\"{P}\""));
+        assert!(dot.contains(
+            "1 [shape=doublecircle, label=\"1\", style=filled, fillcolor=palegreen, penwidth=2]"
+        ));
+        assert!(dot.contains("3 [label=\"3\", style=filled, fillcolor=lightpink]"));
+        assert!(dot.contains("  1 -> 2 [label=\" a\", color=red, penwidth=2];"));
+    }
+}
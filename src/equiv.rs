@@ -0,0 +1,465 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{act::Act, bisim::Partition, ctl::Fresh, dense::Transitions, mu::Mu, ts::Ts};
+
+/// Relations between transition systems
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Relation<A> {
+    /// Strong bisimilarity
+    Strong,
+    /// Weak bisimilarity, the given action being silent
+    Weak(A),
+    /// Simulation preorder: the first system is simulated by the second one
+    Simulation,
+    /// Trace preorder: the traces of the first system are traces of the second one.
+    /// Traces alternate the labels of the states and the actions of the transitions.
+    Traces,
+}
+
+/// Reason why two systems are not related
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distinction<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    /// A formula holding in every initial state of one of the systems,
+    /// and violated by an initial state of the other one
+    pub formula: Mu<A, P>,
+    /// Whether the formula holds in the first system
+    pub first: bool,
+}
+
+fn truth<A, P>(fresh: &mut Fresh) -> Mu<A, P>
+where
+    A: Display + Clone,
+    P: Display,
+{
+    let x = fresh.next();
+    Mu::Gfp(x.clone(), Box::new(Mu::Var(x)))
+}
+
+fn conjunction<A, P>(formulas: Vec<Mu<A, P>>, fresh: &mut Fresh) -> Mu<A, P>
+where
+    A: Display + Clone,
+    P: Display,
+{
+    formulas
+        .into_iter()
+        .reduce(|a, b| Mu::And(Box::new(a), Box::new(b)))
+        .unwrap_or_else(|| truth(fresh))
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Disjoint union of two systems, and the offset added to the states of the second one
+    fn union(&self, other: &Ts<A, P>) -> (Ts<A, P>, u32) {
+        let offset = self.states.iter().max().map_or(0, |s| s + 1);
+        let shift = |s: &u32| s + offset;
+        let mut union = self.clone();
+        union.states.extend(other.states.iter().map(shift));
+        union.initial.extend(other.initial.iter().map(shift));
+        let shifted = other
+            .transitions
+            .iter()
+            .map(|(s, a, t)| (shift(s), a.clone(), shift(t)));
+        union.transitions = Transitions::new(
+            &union.states,
+            self.transitions
+                .iter()
+                .map(|(s, a, t)| (*s, a.clone(), *t))
+                .chain(shifted),
+        );
+        union.labels = union.transitions.to_label_bits(
+            self.states
+                .iter()
+                .map(|s| (*s, self.label(s)))
+                .chain(other.states.iter().map(|s| (shift(s), other.label(s)))),
+        );
+        union.spec = vec![];
+        (union, offset)
+    }
+
+    /// The system whose transitions are the weak transitions of this one:
+    /// silent steps become sequences of silent steps (possibly empty),
+    /// and other steps may be preceded and followed by silent steps.
+    fn saturate(&self, tau: &A) -> Ts<A, P> {
+        let closure = |s: u32| {
+            let mut seen = HashSet::from([s]);
+            let mut queue = VecDeque::from([s]);
+            while let Some(x) = queue.pop_front() {
+                for y in self.succ(&x, tau) {
+                    if seen.insert(*y) {
+                        queue.push_back(*y);
+                    }
+                }
+            }
+            seen
+        };
+        let closures = self
+            .states
+            .iter()
+            .map(|s| (*s, closure(*s)))
+            .collect::<HashMap<_, _>>();
+        let mut saturated = self.clone();
+        let mut transitions = vec![];
+        for s in self.states.iter() {
+            transitions.extend(closures[s].iter().map(|t| (*s, tau.clone(), *t)));
+            for x in closures[s].iter() {
+                for (a, y) in self.transitions.post(x) {
+                    if a == tau {
+                        continue;
+                    }
+                    transitions.extend(closures[y].iter().map(|t| (*s, a.clone(), *t)));
+                }
+            }
+        }
+        saturated.transitions = Transitions::new(&self.states, transitions);
+        saturated
+    }
+
+    /// A literal holding in `s` and not in `t`, which have different labels
+    fn label_formula(&self, s: u32, t: u32) -> Mu<A, P> {
+        let (ls, lt) = (self.label(&s), self.label(&t));
+        match ls.difference(&lt).next() {
+            Some(p) => Mu::Lit(p.clone()),
+            None => Mu::Neg(Box::new(Mu::Lit(
+                lt.difference(&ls).next().unwrap().clone(),
+            ))),
+        }
+    }
+
+    /// A formula holding in `s` and not in `t`, which are not bisimilar.
+    /// `levels` are the partitions computed by the refinement of the bisimulation,
+    /// and `modality` builds the diamond of an action.
+    fn bisim_formula<M>(
+        &self,
+        levels: &[Partition],
+        s: u32,
+        t: u32,
+        modality: &M,
+        fresh: &mut Fresh,
+    ) -> Mu<A, P>
+    where
+        M: Fn(&A, Mu<A, P>, &mut Fresh) -> Mu<A, P>,
+    {
+        let i = levels.iter().position(|p| p[&s] != p[&t]).unwrap();
+        if i == 0 {
+            return self.label_formula(s, t);
+        }
+        let prev = &levels[i - 1];
+        // A transition of `s` that `t` cannot match at the previous level
+        let witness = self
+            .transitions
+            .post(&s)
+            .map(|(a, s1)| (a, *s1))
+            .find(|(a, s1)| self.succ(&t, a).all(|t1| prev[t1] != prev[s1]));
+        match witness {
+            Some((a, s1)) => {
+                let succ = self.succ(&t, a).copied().collect::<Vec<_>>();
+                let conj = succ
+                    .into_iter()
+                    .map(|t1| self.bisim_formula(levels, s1, t1, modality, fresh))
+                    .collect();
+                let conj = conjunction(conj, fresh);
+                modality(a, conj, fresh)
+            }
+            None => Mu::Neg(Box::new(self.bisim_formula(levels, t, s, modality, fresh))),
+        }
+    }
+
+    /// Level at which each pair of states of `left` and `right` is found
+    /// not to be in the simulation preorder (pairs in the preorder are absent)
+    fn simulation_levels(&self, left: &[u32], right: &[u32]) -> HashMap<(u32, u32), usize> {
+        let mut levels = HashMap::new();
+        for s in left {
+            for t in right {
+                if self.label(s) != self.label(t) {
+                    levels.insert((*s, *t), 0);
+                }
+            }
+        }
+        for i in 1.. {
+            let mut removed = vec![];
+            for s in left {
+                for t in right {
+                    if levels.contains_key(&(*s, *t)) {
+                        continue;
+                    }
+                    let unmatched = self
+                        .transitions
+                        .post(s)
+                        .any(|(a, s1)| self.succ(t, a).all(|t1| levels.contains_key(&(*s1, *t1))));
+                    if unmatched {
+                        removed.push((*s, *t));
+                    }
+                }
+            }
+            if removed.is_empty() {
+                break;
+            }
+            levels.extend(removed.into_iter().map(|pair| (pair, i)));
+        }
+        levels
+    }
+
+    /// A formula holding in `s` and not in `t`, where `s` is not simulated by `t`.
+    /// The formula only uses literals, conjunctions and diamonds.
+    fn simulation_formula(
+        &self,
+        levels: &HashMap<(u32, u32), usize>,
+        s: u32,
+        t: u32,
+        fresh: &mut Fresh,
+    ) -> Mu<A, P> {
+        let i = levels[&(s, t)];
+        if i == 0 {
+            return self.label_formula(s, t);
+        }
+        let below = |pair| levels.get(&pair).is_some_and(|j| *j < i);
+        let (a, s1) = self
+            .transitions
+            .post(&s)
+            .map(|(a, s1)| (a, *s1))
+            .find(|(a, s1)| self.succ(&t, a).all(|t1| below((*s1, *t1))))
+            .unwrap();
+        let succ = self.succ(&t, a).copied().collect::<Vec<_>>();
+        let conj = succ
+            .into_iter()
+            .map(|t1| self.simulation_formula(levels, s1, t1, fresh))
+            .collect();
+        Mu::Ex(Act::One(a.clone()), Box::new(conjunction(conj, fresh)))
+    }
+
+    /// A trace of a state of `left` which is not a trace of any state of `right`,
+    /// as a formula holding in its first state
+    fn trace_formula(&self, left: &[u32], right: &[u32], fresh: &mut Fresh) -> Option<Mu<A, P>> {
+        type Node = (u32, Vec<u32>);
+        let matching = |s: &u32, targets: &mut dyn Iterator<Item = u32>| {
+            let mut targets = targets
+                .filter(|t| self.label(t) == self.label(s))
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets.dedup();
+            targets
+        };
+        let mut parent = HashMap::<Node, Option<(Node, A)>>::new();
+        let mut queue = VecDeque::new();
+        let mut failure = None;
+        for s in left {
+            let node = (*s, matching(s, &mut right.iter().copied()));
+            if parent.insert(node.clone(), None).is_none() {
+                queue.push_back(node);
+            }
+        }
+        if let Some(node) = parent.keys().find(|(_, set)| set.is_empty()) {
+            failure = Some(node.clone());
+        }
+        while let (None, Some(node)) = (&failure, queue.pop_front()) {
+            for (a, s1) in self.transitions.post(&node.0) {
+                let mut targets = node.1.iter().flat_map(|t| self.succ(t, a).copied());
+                let next = (*s1, matching(s1, &mut targets));
+                if parent.contains_key(&next) {
+                    continue;
+                }
+                parent.insert(next.clone(), Some((node.clone(), a.clone())));
+                if next.1.is_empty() && failure.is_none() {
+                    failure = Some(next.clone());
+                }
+                queue.push_back(next);
+            }
+        }
+        // The propositions holding exactly in the states of the trace
+        let props = self.labels.keys().cloned().collect::<HashSet<P>>();
+        let exact = |s: u32| {
+            let label = self.label(&s);
+            props
+                .iter()
+                .map(|p| match label.contains(p) {
+                    true => Mu::Lit(p.clone()),
+                    false => Mu::Neg(Box::new(Mu::Lit(p.clone()))),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut node = failure?;
+        let mut formula = conjunction(exact(node.0), fresh);
+        while let Some((prev, a)) = parent[&node].clone() {
+            let mut conj = exact(prev.0);
+            conj.push(Mu::Ex(Act::One(a), Box::new(formula)));
+            formula = conjunction(conj, fresh);
+            node = prev;
+        }
+        Some(formula)
+    }
+
+    /// Check whether `self` and `other` are related by `relation`.
+    ///
+    /// For the preorders, `self` is expected to be below `other`.
+    /// When the systems are not related, returns a μ-calculus formula telling them apart.
+    pub fn compare(
+        &self,
+        other: &Ts<A, P>,
+        relation: &Relation<A>,
+    ) -> Result<(), Distinction<A, P>> {
+        let (union, offset) = self.union(other);
+        let left = self.initial.iter().copied().collect::<Vec<_>>();
+        let right = other.initial.iter().map(|t| t + offset).collect::<Vec<_>>();
+        let fresh = &mut Fresh(0);
+        // A state of one system which is not related to any initial state of the other one
+        let unmatched = |from: &[u32], to: &[u32], related: &dyn Fn(u32, u32) -> bool| {
+            from.iter()
+                .find(|s| !to.iter().any(|t| related(**s, *t)))
+                .copied()
+        };
+        // Formula violated by `s` and holding in all the states of `to`,
+        // from formulas holding in `s` and not in these states
+        let mut separate =
+            |s: u32, to: &[u32], formula: &mut dyn FnMut(u32, u32, &mut Fresh) -> Mu<A, P>| {
+                let conj = to.iter().map(|t| formula(s, *t, fresh)).collect();
+                Mu::Neg(Box::new(conjunction(conj, fresh)))
+            };
+        match relation {
+            Relation::Strong | Relation::Weak(_) => {
+                let system = match relation {
+                    Relation::Weak(tau) => union.saturate(tau),
+                    _ => union,
+                };
+                let levels = system.strong_refinements();
+                let stable = levels.last().unwrap();
+                let related = |s: u32, t: u32| stable[&s] == stable[&t];
+                let modality = |a: &A, mu: Mu<A, P>, fresh: &mut Fresh| match relation {
+                    Relation::Weak(tau) => {
+                        // Some silent steps, the action (unless it is silent), some silent steps
+                        let silent = |mu: Mu<A, P>, fresh: &mut Fresh| {
+                            let x = fresh.next();
+                            let step = Mu::Ex(Act::One(tau.clone()), Box::new(Mu::Var(x.clone())));
+                            Mu::Lfp(x, Box::new(Mu::Or(Box::new(mu), Box::new(step))))
+                        };
+                        if a == tau {
+                            silent(mu, fresh)
+                        } else {
+                            let mu = Mu::Ex(Act::One(a.clone()), Box::new(silent(mu, fresh)));
+                            silent(mu, fresh)
+                        }
+                    }
+                    _ => Mu::Ex(Act::One(a.clone()), Box::new(mu)),
+                };
+                let mut formula =
+                    |s, t, fresh: &mut Fresh| system.bisim_formula(&levels, s, t, &modality, fresh);
+                if let Some(s) = unmatched(&left, &right, &related) {
+                    let formula = separate(s, &right, &mut formula);
+                    return Err(Distinction {
+                        formula,
+                        first: false,
+                    });
+                }
+                if let Some(t) = unmatched(&right, &left, &related) {
+                    let formula = separate(t, &left, &mut formula);
+                    return Err(Distinction {
+                        formula,
+                        first: true,
+                    });
+                }
+                Ok(())
+            }
+            Relation::Simulation => {
+                let states = self.states.iter().copied().collect::<Vec<_>>();
+                let targets = other.states.iter().map(|t| t + offset).collect::<Vec<_>>();
+                let levels = union.simulation_levels(&states, &targets);
+                let related = |s: u32, t: u32| !levels.contains_key(&(s, t));
+                match unmatched(&left, &right, &related) {
+                    Some(s) => {
+                        let mut formula = |s, t, fresh: &mut Fresh| {
+                            union.simulation_formula(&levels, s, t, fresh)
+                        };
+                        let formula = separate(s, &right, &mut formula);
+                        Err(Distinction {
+                            formula,
+                            first: false,
+                        })
+                    }
+                    None => Ok(()),
+                }
+            }
+            Relation::Traces => match union.trace_formula(&left, &right, fresh) {
+                Some(formula) => Err(Distinction {
+                    formula: Mu::Neg(Box::new(formula)),
+                    first: false,
+                }),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Check whether `self` and `other` are strongly bisimilar,
+    /// returning a formula telling them apart when they are not
+    pub fn bisimilar(&self, other: &Ts<A, P>) -> Result<(), Distinction<A, P>> {
+        self.compare(other, &Relation::Strong)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{equiv::Relation, spec::Spec, ts::Ts};
+
+    fn ts(trans: Vec<(u32, Vec<(char, u32)>)>) -> Ts<char, char> {
+        let mut states = trans
+            .iter()
+            .flat_map(|(s, post)| post.iter().map(|(_, t)| *t).chain([*s]))
+            .collect::<Vec<_>>();
+        states.sort();
+        states.dedup();
+        Ts::new::<Spec<char, char>>(states, vec![1], vec![], trans, vec![])
+    }
+
+    /// Check that a distinction tells the systems apart
+    fn check(left: &Ts<char, char>, right: &Ts<char, char>, relation: Relation<char>) {
+        let distinction = left.compare(right, &relation).unwrap_err();
+        let holds = |ts: &Ts<char, char>| {
+            let sat = ts.sat(&distinction.formula, Default::default());
+            ts.initial.iter().all(|s| sat.contains(s))
+        };
+        assert_eq!(holds(left), distinction.first);
+        assert_eq!(holds(right), !distinction.first);
+    }
+
+    #[test]
+    fn test_1() {
+        // a.(b + c) and a.b + a.c have the same traces but are not bisimilar
+        let p = ts(vec![(1, vec![('a', 2)]), (2, vec![('b', 3), ('c', 4)])]);
+        let q = ts(vec![
+            (1, vec![('a', 2), ('a', 3)]),
+            (2, vec![('b', 4)]),
+            (3, vec![('c', 5)]),
+        ]);
+        assert!(p.compare(&q, &Relation::Traces).is_ok());
+        assert!(q.compare(&p, &Relation::Traces).is_ok());
+        assert!(q.compare(&p, &Relation::Simulation).is_ok());
+        check(&p, &q, Relation::Simulation);
+        check(&p, &q, Relation::Strong);
+        check(&q, &p, Relation::Strong);
+        assert!(p.bisimilar(&p.minimize()).is_ok());
+    }
+
+    #[test]
+    fn test_2() {
+        // t.a and a are weakly bisimilar, t.a + b and a + b are not
+        let p = ts(vec![(1, vec![('t', 2)]), (2, vec![('a', 3)])]);
+        let q = ts(vec![(1, vec![('a', 2)])]);
+        assert!(p.compare(&q, &Relation::Weak('t')).is_ok());
+        check(&p, &q, Relation::Strong);
+        check(&p, &q, Relation::Traces);
+        let p = ts(vec![(1, vec![('t', 2), ('b', 3)]), (2, vec![('a', 3)])]);
+        let q = ts(vec![(1, vec![('a', 2), ('b', 2)])]);
+        check(&p, &q, Relation::Weak('t'));
+        check(&q, &p, Relation::Weak('t'));
+    }
+}
@@ -0,0 +1,847 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+    rc::Rc,
+};
+
+use crate::{
+    ctl::Ctl,
+    mu::Mu,
+    spec::Spec,
+    ts::{Backend, Ts},
+};
+
+/// A path in a transition system.
+///
+/// The path starts in `start` and follows `steps`.
+/// If `lasso` is `Some(i)`, the last state of the path is also its `i`-th state
+/// and the path loops forever through the steps following it.
+/// Otherwise the path is finite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace<A> {
+    pub start: u32,
+    pub steps: Vec<(A, u32)>,
+    pub lasso: Option<usize>,
+}
+
+impl<A> Trace<A>
+where
+    A: Clone,
+{
+    /// The empty path starting (and ending) in a given state
+    pub fn new(start: u32) -> Self {
+        Trace {
+            start,
+            steps: vec![],
+            lasso: None,
+        }
+    }
+
+    /// The sequence of states visited by the path
+    pub fn states(&self) -> Vec<u32> {
+        let mut states = vec![self.start];
+        states.extend(self.steps.iter().map(|(_, s)| *s));
+        states
+    }
+
+    /// The last state of the path
+    pub fn last(&self) -> u32 {
+        self.steps.last().map_or(self.start, |(_, s)| *s)
+    }
+
+    /// Append a path starting in the last state of this (finite) path
+    fn append(&mut self, other: Trace<A>) {
+        let offset = self.steps.len();
+        self.steps.extend(other.steps);
+        self.lasso = other.lasso.map(|i| i + offset);
+    }
+}
+
+/// A value displayed with the names of the states of a transition system,
+/// see [`Ts::named`]
+pub struct Named<'a, T> {
+    value: &'a T,
+    names: &'a HashMap<u32, String>,
+}
+
+impl<'a, T> Named<'a, T> {
+    fn name(&self, x: u32) -> String {
+        self.names.get(&x).cloned().unwrap_or_else(|| x.to_string())
+    }
+
+    fn with<U>(&self, value: &'a U) -> Named<'a, U> {
+        Named {
+            value,
+            names: self.names,
+        }
+    }
+}
+
+impl<A> Display for Named<'_, Trace<A>>
+where
+    A: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let trace = self.value;
+        write!(f, "{}", self.name(trace.start))?;
+        for (a, s) in trace.steps.iter() {
+            write!(f, " -{}-> {}", a, self.name(*s))?;
+        }
+        match trace.lasso {
+            Some(i) => write!(f, " (loops back to step {})", i),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A> Display for Trace<A>
+where
+    A: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = HashMap::new();
+        write!(
+            f,
+            "{}",
+            Named {
+                value: self,
+                names: &names
+            }
+        )
+    }
+}
+
+/// A violated specification, explained in one of the initial states
+pub type Violation<'s, A, P> = (&'s Spec<A, P>, Explanation<A, P>);
+
+/// A position of the verification game:
+/// a state and the formula to verify in this state
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    pub state: u32,
+    pub formula: Mu<A, P>,
+}
+
+/// A winning strategy of the verifier in the verification game
+/// of a μ-calculus formula.
+///
+/// Only the positions reachable from the initial position `positions[0]`
+/// are recorded. `moves[i]` are the moves allowed from `positions[i]`:
+/// exactly one where the verifier chooses (disjunctions and diamonds),
+/// all of them where the refuter chooses (conjunctions and boxes).
+/// Every infinite play following the strategy unfolds greatest fixpoints
+/// infinitely often.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Strategy<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    pub positions: Vec<Position<A, P>>,
+    pub moves: Vec<Vec<usize>>,
+}
+
+impl<A, P> Display for Named<'_, Strategy<A, P>>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let strategy = self.value;
+        for (i, (pos, moves)) in strategy
+            .positions
+            .iter()
+            .zip(strategy.moves.iter())
+            .enumerate()
+        {
+            write!(f, "  #{} {} ⊨ {}", i, self.name(pos.state), pos.formula)?;
+            if !moves.is_empty() {
+                let moves: Vec<String> = moves.iter().map(|j| format!("#{}", j)).collect();
+                write!(f, " → {}", moves.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A, P> Display for Strategy<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = HashMap::new();
+        write!(
+            f,
+            "{}",
+            Named {
+                value: self,
+                names: &names
+            }
+        )
+    }
+}
+
+/// Evidence supporting the result of a verification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Evidence<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    /// A path witnessing an existential property (or violating a universal one)
+    Trace(Trace<A>),
+    /// A winning strategy for the formula (or for its negation)
+    Strategy(Strategy<A, P>),
+}
+
+/// Explanation of why a specification holds (or not) in a given state
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    pub state: u32,
+    pub holds: bool,
+    pub evidence: Evidence<A, P>,
+}
+
+impl<A, P> Display for Named<'_, Explanation<A, P>>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.value.evidence, self.value.holds) {
+            (Evidence::Trace(trace), true) => write!(f, "witness: {}", self.with(trace)),
+            (Evidence::Trace(trace), false) => {
+                write!(f, "counterexample: {}", self.with(trace))
+            }
+            (Evidence::Strategy(strategy), true) => {
+                write!(
+                    f,
+                    "winning strategy of the verifier:\n{}",
+                    self.with(strategy)
+                )
+            }
+            (Evidence::Strategy(strategy), false) => {
+                write!(
+                    f,
+                    "winning strategy of the refuter:\n{}",
+                    self.with(strategy)
+                )
+            }
+        }
+    }
+}
+
+impl<A, P> Display for Explanation<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = HashMap::new();
+        write!(
+            f,
+            "{}",
+            Named {
+                value: self,
+                names: &names
+            }
+        )
+    }
+}
+
+/// A fixpoint variable in scope during the construction of a strategy
+#[derive(Clone)]
+struct Frame<'f, A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    binder: &'f Mu<A, P>,
+    /// Approximants of a least fixpoint, or the greatest fixpoint
+    approx: Rc<Vec<HashSet<u32>>>,
+    /// The approximant currently bound to the variable
+    index: usize,
+}
+
+type Key = (usize, Vec<usize>);
+
+/// A position left to explore, with the formula and the variables in scope
+type Task<'f, A, P> = (usize, &'f Mu<A, P>, Vec<Frame<'f, A, P>>);
+
+/// Construction of a winning strategy for the verifier.
+///
+/// Least fixpoints are unfolded using their approximants: a state of the
+/// `i`-th approximant is explained with the variable bound to the
+/// `(i-1)`-th approximant. Plays can thus only loop through greatest
+/// fixpoints.
+struct Prover<'t, 'f, A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    ts: &'t Ts<A, P>,
+    strategy: Strategy<A, P>,
+    index: HashMap<(u32, Key), usize>,
+    todo: Vec<Task<'f, A, P>>,
+    approx: HashMap<Key, Rc<Vec<HashSet<u32>>>>,
+    sat: HashMap<Key, HashSet<u32>>,
+}
+
+impl<'t, 'f, A, P> Prover<'t, 'f, A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    fn key(mu: &Mu<A, P>, frames: &[Frame<'f, A, P>]) -> Key {
+        (
+            mu as *const Mu<A, P> as usize,
+            frames.iter().map(|f| f.index).collect(),
+        )
+    }
+
+    fn env(frames: &[Frame<'f, A, P>]) -> HashMap<String, HashSet<u32>> {
+        let mut env = HashMap::new();
+        for frame in frames {
+            if let Mu::Lfp(x, _) | Mu::Gfp(x, _) = frame.binder {
+                env.insert(x.clone(), frame.approx[frame.index].clone());
+            }
+        }
+        env
+    }
+
+    fn sat(&mut self, mu: &'f Mu<A, P>, frames: &[Frame<'f, A, P>]) -> &HashSet<u32> {
+        let ts = self.ts;
+        self.sat
+            .entry(Self::key(mu, frames))
+            .or_insert_with(|| ts.sat(mu, Self::env(frames)))
+    }
+
+    /// Bind the variable of `binder` to the approximant explaining `state`
+    fn frame(
+        &mut self,
+        binder: &'f Mu<A, P>,
+        frames: &[Frame<'f, A, P>],
+        state: u32,
+    ) -> Frame<'f, A, P> {
+        let ts = self.ts;
+        let approx = self
+            .approx
+            .entry(Self::key(binder, frames))
+            .or_insert_with(|| {
+                let env = Self::env(frames);
+                match binder {
+                    Mu::Lfp(x, body) => {
+                        let mut approx = vec![HashSet::new()];
+                        loop {
+                            let mut env = env.clone();
+                            env.insert(x.clone(), approx.last().unwrap().clone());
+                            let next = ts.sat(body, env);
+                            if next == *approx.last().unwrap() {
+                                break;
+                            }
+                            approx.push(next);
+                        }
+                        Rc::new(approx)
+                    }
+                    _ => Rc::new(vec![ts.sat(binder, env)]),
+                }
+            })
+            .clone();
+        let index = match binder {
+            Mu::Lfp(_, _) => approx
+                .iter()
+                .position(|s| s.contains(&state))
+                .map_or(0, |rank| rank.max(1) - 1),
+            _ => 0,
+        };
+        Frame {
+            binder,
+            approx,
+            index,
+        }
+    }
+
+    fn position(&mut self, state: u32, mu: &'f Mu<A, P>, frames: Vec<Frame<'f, A, P>>) -> usize {
+        let key = (state, Self::key(mu, &frames));
+        if let Some(i) = self.index.get(&key) {
+            return *i;
+        }
+        let i = self.strategy.positions.len();
+        self.strategy.positions.push(Position {
+            state,
+            formula: mu.clone(),
+        });
+        self.strategy.moves.push(vec![]);
+        self.index.insert(key, i);
+        self.todo.push((i, mu, frames));
+        i
+    }
+
+    fn unfold(
+        &mut self,
+        state: u32,
+        binder: &'f Mu<A, P>,
+        mut frames: Vec<Frame<'f, A, P>>,
+    ) -> usize {
+        let frame = self.frame(binder, &frames, state);
+        frames.push(frame);
+        match binder {
+            Mu::Lfp(_, body) | Mu::Gfp(_, body) => self.position(state, body, frames),
+            _ => unreachable!(),
+        }
+    }
+
+    fn explore(&mut self, i: usize, mu: &'f Mu<A, P>, frames: Vec<Frame<'f, A, P>>) {
+        let state = self.strategy.positions[i].state;
+        let moves = match mu {
+            Mu::Lit(_) | Mu::Neg(_) => vec![],
+            Mu::Var(x) => {
+                let bound = frames.iter().rposition(|f| match f.binder {
+                    Mu::Lfp(y, _) | Mu::Gfp(y, _) => x == y,
+                    _ => false,
+                });
+                match bound {
+                    Some(j) => {
+                        let binder = frames[j].binder;
+                        vec![self.unfold(state, binder, frames[..j].to_vec())]
+                    }
+                    None => vec![],
+                }
+            }
+            Mu::Lfp(_, _) | Mu::Gfp(_, _) => vec![self.unfold(state, mu, frames)],
+            Mu::And(a, b) => vec![
+                self.position(state, a, frames.clone()),
+                self.position(state, b, frames),
+            ],
+            Mu::Or(a, b) => {
+                let next = if self.sat(a, &frames).contains(&state) {
+                    a
+                } else {
+                    b
+                };
+                vec![self.position(state, next, frames)]
+            }
+            Mu::All(act, a) | Mu::AllPred(act, a) => {
+                let ts = self.ts;
+                ts.adjacent(&state, act, matches!(mu, Mu::AllPred(_, _)))
+                    .map(|t| self.position(*t, a, frames.clone()))
+                    .collect()
+            }
+            Mu::Ex(act, a) | Mu::ExPred(act, a) => {
+                let ts = self.ts;
+                let sat_a = self.sat(a, &frames).clone();
+                ts.adjacent(&state, act, matches!(mu, Mu::ExPred(_, _)))
+                    .find(|t| sat_a.contains(t))
+                    .map(|t| self.position(*t, a, frames))
+                    .into_iter()
+                    .collect()
+            }
+        };
+        self.strategy.moves[i] = moves;
+    }
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// A winning strategy of the verifier for a closed formula
+    /// in positive normal form, satisfied by `state`
+    fn strategy(&self, mu: &Mu<A, P>, state: u32) -> Strategy<A, P> {
+        let mut prover = Prover {
+            ts: self,
+            strategy: Strategy {
+                positions: vec![],
+                moves: vec![],
+            },
+            index: HashMap::new(),
+            todo: vec![],
+            approx: HashMap::new(),
+            sat: HashMap::new(),
+        };
+        prover.position(state, mu, vec![]);
+        while let Some((i, mu, frames)) = prover.todo.pop() {
+            prover.explore(i, mu, frames);
+        }
+        prover.strategy
+    }
+
+    /// The transitions leaving a state
+    fn edges<'a>(&'a self, x: &u32) -> impl Iterator<Item = (&'a A, &'a u32)> + 'a {
+        self.transitions.post(x)
+    }
+
+    /// A shortest path from `from` to a state of `target`,
+    /// visiting only states of `within` before reaching `target`
+    fn path_to(&self, from: u32, target: &HashSet<u32>, within: &HashSet<u32>) -> Trace<A> {
+        let mut parent = HashMap::<u32, (A, u32)>::new();
+        let mut todo = VecDeque::from([from]);
+        let mut seen = HashSet::from([from]);
+        let mut end = from;
+        while let Some(x) = todo.pop_front() {
+            if target.contains(&x) {
+                end = x;
+                break;
+            }
+            if !within.contains(&x) {
+                continue;
+            }
+            for (a, y) in self.edges(&x) {
+                if seen.insert(*y) {
+                    parent.insert(*y, (a.clone(), x));
+                    todo.push_back(*y);
+                }
+            }
+        }
+        let mut steps = vec![];
+        while let Some((a, x)) = parent.get(&end) {
+            steps.push((a.clone(), end));
+            end = *x;
+        }
+        steps.reverse();
+        Trace {
+            start: from,
+            steps,
+            lasso: None,
+        }
+    }
+
+    /// A (fair) maximal path from `from` staying forever in `within`, which must
+    /// contain only states satisfying `EG within`
+    fn lasso(&self, from: u32, within: &HashSet<u32>) -> Trace<A> {
+        let components = self.fair_components(self, within, &[]);
+        let target: HashSet<u32> = components.iter().flatten().cloned().collect();
+        let mut trace = self.path_to(from, &target, within);
+        let last = trace.last();
+        let component = components.iter().find(|c| c.contains(&last)).unwrap();
+        let lasso = trace.steps.len();
+        for (a, y) in self.fair_cycle(self, last, component, &[]) {
+            match a {
+                Some(a) => trace.steps.push((a, y)),
+                // The path ends in a deadlock
+                None => return trace,
+            }
+        }
+        trace.lasso = Some(lasso);
+        trace
+    }
+
+    fn holds_ctl(&self, ctl: &Ctl<A, P>, pos: bool, state: u32) -> bool {
+        self.sat_ctl(ctl).contains(&state) == pos
+    }
+
+    /// A path explaining why `state` satisfies `ctl` (if `pos`) or its negation.
+    /// Universal properties that hold are explained by the empty path.
+    fn ctl_trace(&self, ctl: &Ctl<A, P>, pos: bool, state: u32) -> Trace<A> {
+        match (ctl, pos) {
+            (Ctl::Lit(_), _) => Trace::new(state),
+            (Ctl::Neg(a), _) => self.ctl_trace(a, !pos, state),
+            (Ctl::And(a, b), true) | (Ctl::Or(a, b), false) => {
+                let trace = self.ctl_trace(a, pos, state);
+                if trace.steps.is_empty() {
+                    self.ctl_trace(b, pos, state)
+                } else {
+                    trace
+                }
+            }
+            (Ctl::Or(a, b), true) | (Ctl::And(a, b), false) => {
+                let next = if self.holds_ctl(a, pos, state) { a } else { b };
+                self.ctl_trace(next, pos, state)
+            }
+            (Ctl::Ex(act, a), true) | (Ctl::Ax(act, a), false) => {
+                let fair = self.fair(self.states.clone());
+                let next = self
+                    .edges(&state)
+                    .filter(|(b, _)| act.as_ref().is_none_or(|act| act == *b))
+                    .find(|(_, t)| fair.contains(t) && self.holds_ctl(a, pos, **t));
+                let mut trace = Trace::new(state);
+                if let Some((b, t)) = next {
+                    trace.steps.push((b.clone(), *t));
+                    trace.append(self.ctl_trace(a, pos, *t));
+                }
+                trace
+            }
+            (Ctl::Ef(a), true) | (Ctl::Ag(a), false) => {
+                let sat_a = self.sat_ctl(a);
+                let target = if pos {
+                    sat_a
+                } else {
+                    self.states.difference(&sat_a).cloned().collect()
+                };
+                let mut trace = self.path_to(state, &self.fair(target), &self.states);
+                trace.append(self.ctl_trace(a, pos, trace.last()));
+                trace
+            }
+            (Ctl::Eu(a, b), true) => {
+                let target = self.fair(self.sat_ctl(b));
+                let mut trace = self.path_to(state, &target, &self.sat_ctl(a));
+                trace.append(self.ctl_trace(b, true, trace.last()));
+                trace
+            }
+            (Ctl::Au(a, b), false) => {
+                let not_a = Box::new(Ctl::Neg(a.clone()));
+                let not_b = Box::new(Ctl::Neg(b.clone()));
+                let bad = Ctl::Eu(not_b.clone(), Box::new(Ctl::And(not_a, not_b.clone())));
+                if self.sat_ctl(&bad).contains(&state) {
+                    self.ctl_trace(&bad, true, state)
+                } else {
+                    self.lasso(state, &self.sat_ctl(&Ctl::Eg(not_b)))
+                }
+            }
+            (Ctl::Eg(_), true) => self.lasso(state, &self.sat_ctl(ctl)),
+            (Ctl::Af(a), false) => {
+                let eg = Ctl::Eg(Box::new(Ctl::Neg(a.clone())));
+                self.lasso(state, &self.sat_ctl(&eg))
+            }
+            (Ctl::Ax(_, _), true)
+            | (Ctl::Ex(_, _), false)
+            | (Ctl::Af(_), true)
+            | (Ctl::Ag(_), true)
+            | (Ctl::Ef(_), false)
+            | (Ctl::Eg(_), false)
+            | (Ctl::Au(_, _), true)
+            | (Ctl::Eu(_, _), false) => Trace::new(state),
+        }
+    }
+
+    /// Display a trace, a strategy or an explanation with the names of the states
+    pub fn named<'a, T>(&'a self, value: &'a T) -> Named<'a, T> {
+        Named {
+            value,
+            names: &self.names,
+        }
+    }
+
+    /// Explain why a specification holds (or not) in a given state.
+    ///
+    /// CTL specifications are explained by a path: a witness of
+    /// existential properties that hold, a counterexample of universal
+    /// properties that fail. LTL specifications that fail are explained
+    /// by a counterexample path.
+    /// μ-calculus specifications are explained by a winning strategy in the
+    /// verification game of the formula, or of its negation if it fails.
+    pub fn explain(&self, spec: &Spec<A, P>, state: u32) -> Explanation<A, P> {
+        self.explain_with(spec, state, Backend::default())
+    }
+
+    /// Explain why a specification holds (or not) in a given state.
+    /// With the game backend, strategies are computed by solving the
+    /// verification game.
+    pub fn explain_with(
+        &self,
+        spec: &Spec<A, P>,
+        state: u32,
+        backend: Backend,
+    ) -> Explanation<A, P> {
+        if matches!(spec, Spec::Mu(_)) && !self.fairness.is_empty() {
+            return self.fair_restriction().explain_with(spec, state, backend);
+        }
+        let holds = self.sat_spec_with(spec, backend).contains(&state);
+        let evidence = match (spec, backend) {
+            (Spec::Ctl(ctl), _) => Evidence::Trace(self.ctl_trace(ctl, holds, state)),
+            (Spec::Ltl(ltl), _) => Evidence::Trace(
+                self.ltl_counterexample(ltl, state)
+                    .unwrap_or_else(|| Trace::new(state)),
+            ),
+            (Spec::Mu(mu), Backend::Fixpoint) => {
+                let mu = if holds {
+                    mu.to_pnf()
+                } else {
+                    Mu::Neg(Box::new(mu.clone())).to_pnf()
+                };
+                Evidence::Strategy(self.strategy(&mu, state))
+            }
+            (Spec::Mu(mu), Backend::Game) => Evidence::Strategy(self.strategy_game(mu, state)),
+        };
+        Explanation {
+            state,
+            holds,
+            evidence,
+        }
+    }
+
+    /// The specifications violated by the system, each one with an
+    /// explanation for one of the initial states violating it
+    pub fn violations(&self) -> Vec<Violation<'_, A, P>> {
+        self.violations_with(Backend::default())
+    }
+
+    /// The specifications violated by the system, using a given backend
+    pub fn violations_with(&self, backend: Backend) -> Vec<Violation<'_, A, P>> {
+        self.spec
+            .iter()
+            .filter_map(|spec| {
+                let sat = self.sat_spec_with(spec, backend);
+                let mut initial: Vec<&u32> = self.initial.iter().collect();
+                initial.sort();
+                initial
+                    .into_iter()
+                    .find(|s| !sat.contains(s))
+                    .map(|s| (spec, self.explain_with(spec, *s, backend)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::act::Act;
+
+    fn system() -> Ts<char, char> {
+        Ts::new(
+            vec![1, 2, 3, 4],
+            vec![1],
+            vec![(1, vec!['A']), (2, vec!['A']), (3, vec!['B'])],
+            vec![
+                (1, vec![('a', 2)]),
+                (2, vec![('a', 1), ('b', 3)]),
+                (3, vec![('a', 4)]),
+            ],
+            Vec::<Spec<char, char>>::new(),
+        )
+    }
+
+    fn lit(p: char) -> Box<Ctl<char, char>> {
+        Box::new(Ctl::Lit(p))
+    }
+
+    #[test]
+    fn test_1() {
+        let ts = system();
+        let spec = Spec::Ctl(Ctl::Ag(lit('A')));
+        let explanation = ts.explain(&spec, 1);
+        assert!(!explanation.holds);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Trace(Trace {
+                start: 1,
+                steps: vec![('a', 2), ('b', 3)],
+                lasso: None
+            })
+        )
+    }
+
+    #[test]
+    fn test_2() {
+        let ts = system();
+        let spec = Spec::Ctl(Ctl::Af(lit('B')));
+        let explanation = ts.explain(&spec, 1);
+        assert!(!explanation.holds);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Trace(Trace {
+                start: 1,
+                steps: vec![('a', 2), ('a', 1)],
+                lasso: Some(0)
+            })
+        )
+    }
+
+    #[test]
+    fn test_3() {
+        let ts = system();
+        let spec = Spec::Ctl(Ctl::Eu(lit('A'), lit('B')));
+        let explanation = ts.explain(&spec, 1);
+        assert!(explanation.holds);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Trace(Trace {
+                start: 1,
+                steps: vec![('a', 2), ('b', 3)],
+                lasso: None
+            })
+        )
+    }
+
+    #[test]
+    fn test_4() {
+        let ts = system();
+        let spec = Spec::Ctl(Ctl::Eg(Box::new(Ctl::Neg(lit('A')))));
+        let explanation = ts.explain(&spec, 3);
+        assert!(explanation.holds);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Trace(Trace {
+                start: 3,
+                steps: vec![('a', 4)],
+                lasso: None
+            })
+        )
+    }
+
+    #[test]
+    fn test_5() {
+        // μX. B ∨ ⟨a⟩X ∨ ⟨b⟩X
+        let x = || Box::new(Mu::Var("X".to_string()));
+        let body = Mu::Or(
+            Box::new(Mu::Lit('B')),
+            Box::new(Mu::Or(
+                Box::new(Mu::Ex(Act::One('a'), x())),
+                Box::new(Mu::Ex(Act::One('b'), x())),
+            )),
+        );
+        let spec = Spec::Mu(Mu::Lfp("X".to_string(), Box::new(body)));
+        let ts = system();
+        let explanation = ts.explain(&spec, 1);
+        assert!(explanation.holds);
+        let Evidence::Strategy(strategy) = explanation.evidence else {
+            panic!()
+        };
+        // The strategy reaches B without looping
+        let states: HashSet<u32> = strategy.positions.iter().map(|p| p.state).collect();
+        assert_eq!(states, HashSet::from([1, 2, 3]));
+        assert!(strategy
+            .positions
+            .iter()
+            .any(|p| p.state == 3 && p.formula == Mu::Lit('B')));
+        assert!(strategy.moves.iter().all(|m| m.len() <= 1));
+    }
+
+    #[test]
+    fn test_6() {
+        // νX. A ∧ [a]X ∧ [b]X fails in 1 since 3 is reachable
+        let x = || Box::new(Mu::Var("X".to_string()));
+        let body = Mu::And(
+            Box::new(Mu::Lit('A')),
+            Box::new(Mu::And(
+                Box::new(Mu::All(Act::One('a'), x())),
+                Box::new(Mu::All(Act::One('b'), x())),
+            )),
+        );
+        let spec = Spec::Mu(Mu::Gfp("X".to_string(), Box::new(body)));
+        let ts = system();
+        let explanation = ts.explain(&spec, 1);
+        assert!(!explanation.holds);
+        let Evidence::Strategy(strategy) = explanation.evidence else {
+            panic!()
+        };
+        // The refuter proves μX. ¬A ∨ ⟨a⟩X ∨ ⟨b⟩X
+        assert!(matches!(strategy.positions[0].formula, Mu::Lfp(_, _)));
+        assert!(strategy
+            .positions
+            .iter()
+            .any(|p| p.state == 3 && p.formula == Mu::Neg(Box::new(Mu::Lit('A')))));
+    }
+}
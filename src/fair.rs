@@ -0,0 +1,404 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{
+    buff::{Buff, ParseError},
+    dense::Transitions,
+    graph,
+    sexpr::Sexpr,
+    ts::Ts,
+};
+
+/// Fairness constraints, restricting the paths over which
+/// path quantifiers range
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fairness<A, P> {
+    /// The states labelled by the proposition are visited infinitely often
+    States(P),
+    /// An action which is continuously enabled from some point on is taken
+    /// infinitely often
+    Weak(A),
+    /// An action which is enabled infinitely often is taken infinitely often
+    Strong(A),
+}
+
+/// A graph whose runs follow the paths of a system: the system itself,
+/// or its product with an automaton.
+/// Nodes stuck in a deadlock loop through a step without action.
+pub(crate) trait Runs<A>
+where
+    A: Clone,
+{
+    /// The steps leaving a node
+    fn next_steps(&self, i: u32) -> Vec<(Option<A>, u32)>;
+
+    /// The state of the system a node stands for
+    fn state_of(&self, i: u32) -> u32;
+
+    /// A shortest path from `from` to a node satisfying `target`, staying
+    /// in `within` and taking at least one step if `nonempty`
+    fn path_within<F>(
+        &self,
+        from: u32,
+        target: F,
+        within: &HashSet<u32>,
+        nonempty: bool,
+    ) -> Vec<(Option<A>, u32)>
+    where
+        F: Fn(u32) -> bool,
+    {
+        if !nonempty && target(from) {
+            return vec![];
+        }
+        // The starting node is only seen once left, so that the path may loop back to it
+        let mut parent = HashMap::<u32, (Option<A>, u32)>::new();
+        let mut todo = VecDeque::from([from]);
+        let mut seen = HashSet::new();
+        let mut end = None;
+        'search: while let Some(i) = todo.pop_front() {
+            for (a, j) in self.next_steps(i) {
+                if within.contains(&j) && seen.insert(j) {
+                    parent.insert(j, (a, i));
+                    if target(j) {
+                        end = Some(j);
+                        break 'search;
+                    }
+                    todo.push_back(j);
+                }
+            }
+        }
+        let mut end = end.unwrap();
+        let mut steps = vec![];
+        loop {
+            let (a, i) = parent[&end].clone();
+            steps.push((a, end));
+            if i == from {
+                break;
+            }
+            end = i;
+        }
+        steps.reverse();
+        steps
+    }
+}
+
+impl<A, P> Runs<A> for Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    fn next_steps(&self, i: u32) -> Vec<(Option<A>, u32)> {
+        if self.is_deadlock(&i) {
+            return vec![(None, i)];
+        }
+        self.transitions
+            .post(&i)
+            .map(|(a, j)| (Some(a.clone()), *j))
+            .collect()
+    }
+
+    fn state_of(&self, i: u32) -> u32 {
+        i
+    }
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Declare fairness constraints
+    pub fn with_fairness(mut self, fairness: Vec<Fairness<A, P>>) -> Self {
+        self.fairness = fairness;
+        self
+    }
+
+    fn is_enabled(&self, x: u32, a: &A) -> bool {
+        self.succ(&x, a).next().is_some()
+    }
+
+    /// A step through an action between two nodes of a component
+    fn taken<G>(&self, runs: &G, component: &HashSet<u32>, a: &A) -> Option<(u32, u32)>
+    where
+        G: Runs<A>,
+    {
+        component.iter().find_map(|i| {
+            runs.next_steps(*i)
+                .into_iter()
+                .find(|(b, j)| b.as_ref() == Some(a) && component.contains(j))
+                .map(|(_, j)| (*i, j))
+        })
+    }
+
+    /// The sets of nodes of `within` in which a run can stay forever,
+    /// visiting each set of `visit` infinitely often and respecting the
+    /// fairness constraints. These sets are strongly connected.
+    ///
+    /// The strongly connected components which do not satisfy a strong
+    /// fairness constraint are refined by removing the nodes where its
+    /// action is enabled.
+    pub(crate) fn fair_components<G>(
+        &self,
+        runs: &G,
+        within: &HashSet<u32>,
+        visit: &[HashSet<u32>],
+    ) -> Vec<HashSet<u32>>
+    where
+        G: Runs<A>,
+    {
+        let sccs = |nodes: &HashSet<u32>| {
+            graph::sccs(nodes.iter().cloned(), |i| {
+                runs.next_steps(i)
+                    .into_iter()
+                    .map(|(_, j)| j)
+                    .filter(|j| nodes.contains(j))
+                    .collect()
+            })
+            .into_iter()
+            .map(|component| component.into_iter().collect::<HashSet<_>>())
+        };
+        let mut todo = sccs(within).collect::<Vec<_>>();
+        let mut fair = vec![];
+        while let Some(component) = todo.pop() {
+            let i = *component.iter().next().unwrap();
+            let cyclic = component.len() > 1 || runs.next_steps(i).iter().any(|(_, j)| *j == i);
+            let visits = visit
+                .iter()
+                .all(|set| component.iter().any(|i| set.contains(i)));
+            if !cyclic || !visits {
+                continue;
+            }
+            let states = component
+                .iter()
+                .map(|i| runs.state_of(*i))
+                .collect::<HashSet<_>>();
+            let mut unfair = HashSet::new();
+            let mut holds = true;
+            for constraint in self.fairness.iter() {
+                match constraint {
+                    Fairness::States(p) => holds &= states.iter().any(|x| self.has_label(x, p)),
+                    Fairness::Weak(a) => {
+                        holds &= self.taken(runs, &component, a).is_some()
+                            || states.iter().any(|x| !self.is_enabled(*x, a))
+                    }
+                    Fairness::Strong(a) => {
+                        if self.taken(runs, &component, a).is_none() {
+                            unfair.extend(
+                                component
+                                    .iter()
+                                    .filter(|i| self.is_enabled(runs.state_of(**i), a)),
+                            );
+                        }
+                    }
+                }
+            }
+            if !holds {
+                continue;
+            }
+            if unfair.is_empty() {
+                fair.push(component);
+            } else {
+                todo.extend(sccs(&component.difference(&unfair).cloned().collect()));
+            }
+        }
+        fair
+    }
+
+    /// A cycle from `entry` through a component returned by
+    /// [`Ts::fair_components`], visiting each set of `visit`
+    /// and respecting the fairness constraints
+    pub(crate) fn fair_cycle<G>(
+        &self,
+        runs: &G,
+        entry: u32,
+        component: &HashSet<u32>,
+        visit: &[HashSet<u32>],
+    ) -> Vec<(Option<A>, u32)>
+    where
+        G: Runs<A>,
+    {
+        let mut steps: Vec<(Option<A>, u32)> = vec![];
+        let last = |steps: &Vec<(Option<A>, u32)>| steps.last().map_or(entry, |(_, j)| *j);
+        for set in visit {
+            steps.extend(runs.path_within(last(&steps), |j| set.contains(&j), component, false));
+        }
+        for constraint in self.fairness.iter() {
+            match constraint {
+                Fairness::States(p) => {
+                    let target = |j| self.has_label(&runs.state_of(j), p);
+                    steps.extend(runs.path_within(last(&steps), target, component, false));
+                }
+                Fairness::Weak(a) | Fairness::Strong(a) => {
+                    if let Some((i, j)) = self.taken(runs, component, a) {
+                        steps.extend(runs.path_within(last(&steps), |k| k == i, component, false));
+                        steps.push((Some(a.clone()), j));
+                    }
+                }
+            }
+        }
+        let nonempty = steps.is_empty();
+        steps.extend(runs.path_within(last(&steps), |j| j == entry, component, nonempty));
+        steps
+    }
+
+    /// The states from which a fair path starts
+    pub(crate) fn fair_states(&self) -> HashSet<u32> {
+        self.sat_eg(&self.states)
+    }
+
+    /// Restrict a set of states to the states from which a fair path starts
+    pub(crate) fn fair(&self, states: HashSet<u32>) -> HashSet<u32> {
+        if self.fairness.is_empty() {
+            return states;
+        }
+        let fair = self.fair_states();
+        states.into_iter().filter(|x| fair.contains(x)).collect()
+    }
+
+    /// The system whose modalities only range over the states
+    /// from which a fair path starts, without fairness constraints.
+    /// As fair paths can be prefixed by any path, an edge leads to such
+    /// a state only if it leaves one, so only the edges leading to
+    /// unfair states are removed
+    pub(crate) fn fair_restriction(&self) -> Self {
+        let fair = self.fair_states();
+        let mut ts = self.clone();
+        ts.fairness = vec![];
+        ts.transitions = Transitions::new(
+            &self.states,
+            self.transitions
+                .iter()
+                .filter(|(_, _, y)| fair.contains(y))
+                .map(|(x, a, y)| (*x, a.clone(), *y)),
+        );
+        ts
+    }
+}
+
+impl Fairness<String, String> {
+    /// Read a fairness constraint: `<prop>`, `weak <act>` or `strong <act>`
+    pub fn parse(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let first = buff.expect_symb()?;
+        if buff.is_empty() {
+            return Ok(Fairness::States(first));
+        }
+        let pos = buff.position();
+        let a = buff.expect_symb()?;
+        match first.as_str() {
+            "weak" => Ok(Fairness::Weak(a)),
+            "strong" => Ok(Fairness::Strong(a)),
+            _ => Err(ParseError::new(pos, "the end of the constraint", a)),
+        }
+    }
+}
+
+impl<A, P> Display for Fairness<A, P>
+where
+    A: Display,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fairness::States(p) => write!(f, "{}", p),
+            Fairness::Weak(a) => write!(f, "weak {}", a),
+            Fairness::Strong(a) => write!(f, "strong {}", a),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        act::Act,
+        ctl::Ctl,
+        explain::{Evidence, Trace},
+        fair::Fairness,
+        mu::Mu,
+        spec::Spec,
+        ts::{Backend, Ts},
+    };
+
+    /// Two processes: 1 -work-> 1 forever, or 1 -other-> 2 (Done), 2 -work-> 2
+    fn system() -> Ts<char, char> {
+        Ts::new::<Spec<char, char>>(
+            vec![1, 2],
+            vec![1],
+            vec![(2, vec!['D'])],
+            vec![(1, vec![('w', 1), ('o', 2)]), (2, vec![('w', 2)])],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_1() {
+        let af = Ctl::Af(Box::new(Ctl::Lit('D')));
+        assert_eq!(system().sat_ctl(&af), HashSet::from([2]));
+        for fairness in [
+            Fairness::Weak('o'),
+            Fairness::Strong('o'),
+            Fairness::States('D'),
+        ] {
+            let ts = system().with_fairness(vec![fairness]);
+            assert_eq!(ts.sat_ctl(&af), HashSet::from([1, 2]));
+        }
+        // No path is fair, so that universal formulas hold vacuously
+        let ts = system().with_fairness(vec![Fairness::States('E')]);
+        assert_eq!(ts.fair_states(), HashSet::new());
+        let eg = Ctl::Eg(Box::new(Ctl::Neg(Box::new(Ctl::Lit('D')))));
+        assert_eq!(ts.sat_ctl(&eg), HashSet::new());
+    }
+
+    #[test]
+    fn test_2() {
+        // Under weak fairness of w, a path may stay forever in 1
+        let ts = system().with_fairness(vec![Fairness::Weak('w'), Fairness::States('D')]);
+        let spec = Spec::Ctl(Ctl::Eg(Box::new(Ctl::Lit('D'))));
+        let explanation = ts.explain(&spec, 2);
+        assert!(explanation.holds);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Trace(Trace {
+                start: 2,
+                steps: vec![('w', 2)],
+                lasso: Some(0),
+            })
+        );
+        let ts = system().with_fairness(vec![Fairness::Strong('o')]);
+        let spec = Spec::Ctl(Ctl::Eg(Box::new(Ctl::Neg(Box::new(Ctl::Lit('D'))))));
+        assert!(!ts.explain(&spec, 1).holds);
+    }
+
+    #[test]
+    fn test_3() {
+        // The step to 3 does not start a fair path when D holds infinitely often
+        let mut ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3],
+            vec![1],
+            vec![(2, vec!['D'])],
+            vec![
+                (1, vec![('a', 2), ('b', 3)]),
+                (2, vec![('a', 2)]),
+                (3, vec![('b', 3)]),
+            ],
+            vec![],
+        );
+        let after_b = Spec::Mu(Mu::Ex(Act::One('b'), Box::new(Mu::constant(true))));
+        let next_d = Spec::Mu(Mu::All(Act::True, Box::new(Mu::Lit('D'))));
+        assert_eq!(ts.sat_spec(&after_b), HashSet::from([1, 3]));
+        assert_eq!(ts.sat_spec(&next_d), HashSet::from([2]));
+        ts = ts.with_fairness(vec![Fairness::States('D')]);
+        for backend in [Backend::Fixpoint, Backend::Game] {
+            assert_eq!(ts.sat_spec_with(&after_b, backend), HashSet::new());
+            assert_eq!(ts.sat_spec_with(&next_d, backend), HashSet::from([1, 2, 3]));
+        }
+        assert!(!ts.explain(&after_b, 1).holds);
+        ts.spec = vec![next_d];
+        assert!(ts.check());
+        assert!(ts.minimize().check());
+    }
+}
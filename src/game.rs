@@ -0,0 +1,520 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{
+    explain::{Position, Strategy},
+    mu::Mu,
+    ts::Ts,
+};
+
+/// Fixpoint variables in scope, with the index of their binder
+type Scope = Vec<(String, usize)>;
+
+/// Players of the verification game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Player {
+    /// The verifier, winning plays of even priority
+    Even,
+    /// The refuter, winning plays of odd priority
+    Odd,
+}
+
+impl Player {
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::Even => Player::Odd,
+            Player::Odd => Player::Even,
+        }
+    }
+
+    fn of_priority(priority: usize) -> Self {
+        if priority.is_multiple_of(2) {
+            Player::Even
+        } else {
+            Player::Odd
+        }
+    }
+}
+
+/// A parity game.
+///
+/// A play is won by the owner of the greatest priority visited infinitely
+/// often. Every position has at least one move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    pub owner: Vec<Player>,
+    pub priority: Vec<usize>,
+    pub moves: Vec<Vec<usize>>,
+}
+
+/// The winner of each position of a game,
+/// with a winning strategy for each of them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub winner: Vec<Player>,
+    /// The move to play in a position,
+    /// for positions owned by their winner
+    pub strategy: Vec<Option<usize>>,
+}
+
+impl Game {
+    fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut pred = vec![vec![]; self.moves.len()];
+        for (v, moves) in self.moves.iter().enumerate() {
+            for w in moves {
+                pred[*w].push(v);
+            }
+        }
+        pred
+    }
+
+    /// Attractor of `target` for `player` in the subgame `region`.
+    /// Strategies to reach `target` are recorded in `strategy`.
+    fn attractor(
+        &self,
+        pred: &[Vec<usize>],
+        player: Player,
+        region: &HashSet<usize>,
+        target: &HashSet<usize>,
+        strategy: &mut [Option<usize>],
+    ) -> HashSet<usize> {
+        let mut attr = target.clone();
+        let mut count = HashMap::<usize, usize>::new();
+        let mut todo: VecDeque<usize> = target.iter().cloned().collect();
+        while let Some(w) = todo.pop_front() {
+            for v in pred[w].iter() {
+                if !region.contains(v) || attr.contains(v) {
+                    continue;
+                }
+                let attracted = if self.owner[*v] == player {
+                    strategy[*v] = Some(w);
+                    true
+                } else {
+                    let left = count.entry(*v).or_insert_with(|| {
+                        self.moves[*v].iter().filter(|u| region.contains(u)).count()
+                    });
+                    *left -= 1;
+                    *left == 0
+                };
+                if attracted {
+                    attr.insert(*v);
+                    todo.push_back(*v);
+                }
+            }
+        }
+        attr
+    }
+
+    /// Zielonka's recursive algorithm on the subgame `region`
+    fn zielonka(
+        &self,
+        pred: &[Vec<usize>],
+        region: &HashSet<usize>,
+        strategy: &mut [Option<usize>],
+    ) -> [HashSet<usize>; 2] {
+        let Some(d) = region.iter().map(|v| self.priority[*v]).max() else {
+            return [HashSet::new(), HashSet::new()];
+        };
+        let player = Player::of_priority(d);
+        let (me, other) = match player {
+            Player::Even => (0, 1),
+            Player::Odd => (1, 0),
+        };
+        let top: HashSet<usize> = region
+            .iter()
+            .filter(|v| self.priority[**v] == d)
+            .cloned()
+            .collect();
+        let attr = self.attractor(pred, player, region, &top, strategy);
+        let sub = region.difference(&attr).cloned().collect();
+        let win = self.zielonka(pred, &sub, strategy);
+        if win[other].is_empty() {
+            for v in top {
+                if self.owner[v] == player {
+                    strategy[v] = self.moves[v].iter().find(|w| region.contains(w)).cloned();
+                }
+            }
+            let mut win = [HashSet::new(), HashSet::new()];
+            win[me] = region.clone();
+            win
+        } else {
+            let attr = self.attractor(pred, player.opponent(), region, &win[other], strategy);
+            let sub = region.difference(&attr).cloned().collect();
+            let mut win = self.zielonka(pred, &sub, strategy);
+            win[other].extend(attr);
+            win
+        }
+    }
+
+    /// Solve the game with Zielonka's recursive algorithm
+    pub fn solve(&self) -> Solution {
+        let pred = self.predecessors();
+        let mut strategy = vec![None; self.moves.len()];
+        let region = (0..self.moves.len()).collect();
+        let [even, _] = self.zielonka(&pred, &region, &mut strategy);
+        let winner: Vec<Player> = (0..self.moves.len())
+            .map(|v| {
+                if even.contains(&v) {
+                    Player::Even
+                } else {
+                    Player::Odd
+                }
+            })
+            .collect();
+        for (v, s) in strategy.iter_mut().enumerate() {
+            if self.owner[v] != winner[v] {
+                *s = None
+            }
+        }
+        Solution { winner, strategy }
+    }
+}
+
+/// The verification game of a μ-calculus formula on a transition system.
+///
+/// Positions are pairs of a state and a subformula. The verifier owns
+/// disjunctions and diamonds, the refuter owns conjunctions and boxes.
+/// Unfolding a fixpoint variable visits a priority that is odd for least
+/// fixpoints, even for greatest fixpoints, and greater for outer fixpoints.
+pub struct ModelGame<'m, A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    pub game: Game,
+    pub states: Vec<u32>,
+    pub formulas: Vec<&'m Mu<A, P>>,
+}
+
+impl<'m, A, P> ModelGame<'m, A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Build the verification game of a closed formula in positive normal form
+    pub fn new(ts: &Ts<A, P>, mu: &'m Mu<A, P>) -> Result<Self, String> {
+        // Subformulas in prefix order, with the binder of each variable
+        // and the binder depth of each fixpoint
+        let mut formulas: Vec<&'m Mu<A, P>> = vec![];
+        let mut binder = HashMap::<usize, usize>::new();
+        let mut depth = HashMap::<usize, usize>::new();
+        let mut todo: Vec<(&'m Mu<A, P>, Scope)> = vec![(mu, vec![])];
+        while let Some((f, scope)) = todo.pop() {
+            let i = formulas.len();
+            formulas.push(f);
+            match f {
+                Mu::Lit(_) => (),
+                Mu::Neg(a) if matches!(**a, Mu::Lit(_)) => (),
+                Mu::Neg(_) => return Err(format!("Formula {} is not in positive normal form", f)),
+                Mu::Var(x) => {
+                    let (_, j) = scope
+                        .iter()
+                        .rev()
+                        .find(|(y, _)| x == y)
+                        .ok_or_else(|| format!("Variable {} is free", x))?;
+                    binder.insert(i, *j);
+                }
+                Mu::And(a, b) | Mu::Or(a, b) => {
+                    todo.push((b, scope.clone()));
+                    todo.push((a, scope));
+                }
+                Mu::All(_, a) | Mu::Ex(_, a) | Mu::AllPred(_, a) | Mu::ExPred(_, a) => {
+                    todo.push((a, scope))
+                }
+                Mu::Lfp(x, a) | Mu::Gfp(x, a) => {
+                    depth.insert(i, scope.len());
+                    let mut scope = scope;
+                    scope.push((x.clone(), i));
+                    todo.push((a, scope));
+                }
+            }
+        }
+        let max_depth = depth.values().max().cloned().unwrap_or(0);
+        let indices: HashMap<*const Mu<A, P>, usize> = formulas
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (*f as *const Mu<A, P>, i))
+            .collect();
+        let index = |f: &Mu<A, P>| indices[&(f as *const Mu<A, P>)];
+
+        let mut states: Vec<u32> = ts.states.iter().cloned().collect();
+        states.sort();
+        let state_index: HashMap<u32, usize> =
+            states.iter().enumerate().map(|(i, s)| (*s, i)).collect();
+        let m = formulas.len();
+        let position = |s: &u32, f: usize| state_index[s] * m + f;
+
+        let mut game = Game {
+            owner: vec![],
+            priority: vec![],
+            moves: vec![],
+        };
+        for s in states.iter() {
+            for (i, f) in formulas.iter().enumerate() {
+                let (owner, moves): (Player, Vec<usize>) = match f {
+                    // The refuter is stuck on true literals, the verifier on false ones
+                    Mu::Lit(p) if ts.label(s).contains(p) => (Player::Odd, vec![]),
+                    Mu::Lit(_) => (Player::Even, vec![]),
+                    Mu::Neg(a) => match &**a {
+                        Mu::Lit(p) if ts.label(s).contains(p) => (Player::Even, vec![]),
+                        _ => (Player::Odd, vec![]),
+                    },
+                    Mu::Var(_) => {
+                        let (Mu::Lfp(_, body) | Mu::Gfp(_, body)) = formulas[binder[&i]] else {
+                            unreachable!()
+                        };
+                        (Player::Even, vec![position(s, index(body))])
+                    }
+                    Mu::Lfp(_, a) | Mu::Gfp(_, a) => (Player::Even, vec![position(s, index(a))]),
+                    Mu::And(a, b) => (
+                        Player::Odd,
+                        vec![position(s, index(a)), position(s, index(b))],
+                    ),
+                    Mu::Or(a, b) => (
+                        Player::Even,
+                        vec![position(s, index(a)), position(s, index(b))],
+                    ),
+                    Mu::All(act, a) | Mu::AllPred(act, a) => (
+                        Player::Odd,
+                        ts.adjacent(s, act, matches!(f, Mu::AllPred(_, _)))
+                            .map(|t| position(t, index(a)))
+                            .collect(),
+                    ),
+                    Mu::Ex(act, a) | Mu::ExPred(act, a) => (
+                        Player::Even,
+                        ts.adjacent(s, act, matches!(f, Mu::ExPred(_, _)))
+                            .map(|t| position(t, index(a)))
+                            .collect(),
+                    ),
+                };
+                let priority = match f {
+                    Mu::Var(_) => {
+                        let j = binder[&i];
+                        let fixpoint = if let Mu::Lfp(_, _) = formulas[j] {
+                            1
+                        } else {
+                            2
+                        };
+                        2 * (max_depth - depth[&j]) + fixpoint
+                    }
+                    _ => 0,
+                };
+                // A player stuck in a dead end loses
+                let (priority, moves) = if moves.is_empty() {
+                    let priority = match owner {
+                        Player::Even => 1,
+                        Player::Odd => 0,
+                    };
+                    (priority, vec![position(s, i)])
+                } else {
+                    (priority, moves)
+                };
+                game.owner.push(owner);
+                game.priority.push(priority);
+                game.moves.push(moves);
+            }
+        }
+        Ok(ModelGame {
+            game,
+            states,
+            formulas,
+        })
+    }
+
+    /// The position of the game for a state and the root formula
+    pub fn root(&self, state: u32) -> Option<usize> {
+        self.states
+            .iter()
+            .position(|s| *s == state)
+            .map(|i| i * self.formulas.len())
+    }
+
+    /// The state and the formula of a position
+    pub fn position(&self, v: usize) -> Position<A, P> {
+        let m = self.formulas.len();
+        Position {
+            state: self.states[v / m],
+            formula: self.formulas[v % m].clone(),
+        }
+    }
+
+    /// The states where the verifier wins the game of the root formula
+    pub fn sat(&self, solution: &Solution) -> HashSet<u32> {
+        self.states
+            .iter()
+            .filter(|s| solution.winner[self.root(**s).unwrap()] == Player::Even)
+            .cloned()
+            .collect()
+    }
+
+    /// The winning strategy of the verifier from the root position of a state,
+    /// restricted to the positions it reaches
+    pub fn strategy(&self, solution: &Solution, state: u32) -> Strategy<A, P> {
+        let mut strategy = Strategy {
+            positions: vec![],
+            moves: vec![],
+        };
+        let Some(root) = self.root(state) else {
+            return strategy;
+        };
+        let mut index = HashMap::from([(root, 0)]);
+        let mut todo = vec![root];
+        strategy.positions.push(self.position(root));
+        strategy.moves.push(vec![]);
+        while let Some(v) = todo.pop() {
+            let next: Vec<usize> = match (self.game.owner[v], solution.strategy[v]) {
+                (Player::Even, Some(w)) => vec![w],
+                _ => self.game.moves[v].clone(),
+            };
+            let mut moves = vec![];
+            for w in next.into_iter().filter(|w| *w != v) {
+                let j = *index.entry(w).or_insert_with(|| {
+                    strategy.positions.push(self.position(w));
+                    strategy.moves.push(vec![]);
+                    todo.push(w);
+                    strategy.positions.len() - 1
+                });
+                moves.push(j);
+            }
+            strategy.moves[index[&v]] = moves;
+        }
+        strategy
+    }
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Compute the set of states satisfying a closed formula
+    /// by solving its verification game.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formula has free variables or is not monotone.
+    pub fn sat_game(&self, spec: &Mu<A, P>) -> HashSet<u32> {
+        let mu = spec.to_pnf();
+        let game = ModelGame::new(self, &mu).unwrap();
+        game.sat(&game.game.solve())
+    }
+
+    /// A winning strategy of the verifier for a closed formula (if it holds
+    /// in `state`) or for its negation, computed by solving the verification game
+    pub fn strategy_game(&self, spec: &Mu<A, P>, state: u32) -> Strategy<A, P> {
+        let holds = self.sat_game(spec).contains(&state);
+        let mu = if holds {
+            spec.to_pnf()
+        } else {
+            Mu::Neg(Box::new(spec.clone())).to_pnf()
+        };
+        let game = ModelGame::new(self, &mu).unwrap();
+        game.strategy(&game.game.solve(), state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::act::Act;
+
+    fn var(x: &str) -> Box<Mu<char, char>> {
+        Box::new(Mu::Var(x.to_string()))
+    }
+
+    fn system() -> Ts<char, char> {
+        Ts::new(
+            vec![1, 2, 3, 4],
+            vec![1],
+            vec![(1, vec!['A']), (3, vec!['A'])],
+            vec![
+                (1, vec![('a', 2)]),
+                (2, vec![('a', 2), ('a', 3), ('b', 4)]),
+                (3, vec![('a', 1)]),
+            ],
+            Vec::<Mu<char, char>>::new(),
+        )
+    }
+
+    /// νX. μY. (A ∧ ⟨a⟩X) ∨ ⟨a⟩Y: some a-path visits A infinitely often
+    fn infinitely_often() -> Mu<char, char> {
+        let body = Mu::Or(
+            Box::new(Mu::And(
+                Box::new(Mu::Lit('A')),
+                Box::new(Mu::Ex(Act::One('a'), var("X"))),
+            )),
+            Box::new(Mu::Ex(Act::One('a'), var("Y"))),
+        );
+        Mu::Gfp(
+            "X".to_string(),
+            Box::new(Mu::Lfp("Y".to_string(), Box::new(body))),
+        )
+    }
+
+    #[test]
+    fn test_1() {
+        // Position 0 (even, priority 1) can move to 1 (odd, priority 2) or loop
+        let game = Game {
+            owner: vec![Player::Even, Player::Odd],
+            priority: vec![1, 2],
+            moves: vec![vec![0, 1], vec![1]],
+        };
+        let solution = game.solve();
+        assert_eq!(solution.winner, vec![Player::Even, Player::Even]);
+        assert_eq!(solution.strategy[0], Some(1));
+    }
+
+    #[test]
+    fn test_2() {
+        let game = Game {
+            owner: vec![Player::Odd, Player::Even],
+            priority: vec![1, 0],
+            moves: vec![vec![0, 1], vec![0]],
+        };
+        let solution = game.solve();
+        assert_eq!(solution.winner, vec![Player::Odd, Player::Odd]);
+        assert_eq!(solution.strategy[0], Some(0));
+    }
+
+    #[test]
+    fn test_3() {
+        let ts = system();
+        let mu = infinitely_often();
+        assert_eq!(ts.sat_game(&mu), HashSet::from([1, 2, 3]));
+        assert_eq!(ts.sat_game(&mu), ts.sat(&mu, HashMap::new()));
+    }
+
+    #[test]
+    fn test_4() {
+        // μX. [a]X ∧ [b]X: all paths are finite
+        let ts = system();
+        let mu = Mu::Lfp(
+            "X".to_string(),
+            Box::new(Mu::And(
+                Box::new(Mu::All(Act::One('a'), var("X"))),
+                Box::new(Mu::All(Act::One('b'), var("X"))),
+            )),
+        );
+        assert_eq!(ts.sat_game(&mu), HashSet::from([4]));
+        let neg = Mu::Neg(Box::new(mu.clone()));
+        assert_eq!(ts.sat_game(&neg), ts.sat(&neg, HashMap::new()));
+    }
+
+    #[test]
+    fn test_5() {
+        let ts = system();
+        let strategy = ts.strategy_game(&infinitely_often(), 1);
+        // The verifier never chooses to stay in 2
+        for (pos, moves) in strategy.positions.iter().zip(strategy.moves.iter()) {
+            if let Mu::Ex(Act::One('a'), _) = pos.formula {
+                assert_eq!(moves.len(), 1);
+                let next = &strategy.positions[moves[0]];
+                assert!(pos.state != 2 || next.state == 3);
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    buff::{Buff, ParseError},
+    sexpr::Sexpr,
+};
+
+/// Values of the variables of a guarded-command program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    Bool,
+}
+
+/// Domain of a variable: booleans or a bounded interval of integers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Domain {
+    Bool,
+    Int(i64, i64),
+}
+
+impl Domain {
+    pub fn typ(&self) -> Type {
+        match self {
+            Domain::Bool => Type::Bool,
+            Domain::Int(_, _) => Type::Int,
+        }
+    }
+
+    pub fn contains(&self, v: Value) -> bool {
+        match (self, v) {
+            (Domain::Bool, Value::Bool(_)) => true,
+            (Domain::Int(min, max), Value::Int(n)) => *min <= n && n <= *max,
+            _ => false,
+        }
+    }
+}
+
+/// Declaration of a variable with its initial value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VarDecl {
+    pub name: String,
+    pub domain: Domain,
+    pub init: Value,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    And,
+    Or,
+}
+
+/// Integer and boolean expressions over the variables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Int(i64),
+    Bool(bool),
+    Var(String),
+    Not(Box<Expr>),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+}
+
+/// A guarded command: when `guard` holds, the transition labelled by
+/// `action` performs all the assignments of `updates` simultaneously
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub action: String,
+    pub guard: Expr,
+    pub updates: Vec<(String, Expr)>,
+}
+
+impl BinOp {
+    fn from_symb(op: &str) -> Option<Self> {
+        match op {
+            "+" => Some(BinOp::Add),
+            "-" => Some(BinOp::Sub),
+            "*" => Some(BinOp::Mul),
+            "/" => Some(BinOp::Div),
+            "<" => Some(BinOp::Lt),
+            "<=" => Some(BinOp::Le),
+            ">" => Some(BinOp::Gt),
+            ">=" => Some(BinOp::Ge),
+            "=" => Some(BinOp::Eq),
+            "and" => Some(BinOp::And),
+            "or" => Some(BinOp::Or),
+            _ => None,
+        }
+    }
+
+    /// Operators accepting more than two arguments
+    fn is_associative(&self) -> bool {
+        matches!(self, BinOp::Add | BinOp::Mul | BinOp::And | BinOp::Or)
+    }
+
+    /// Apply the operator to well-typed values,
+    /// or fail on overflows and divisions by zero
+    pub fn apply(&self, lhs: Value, rhs: Value) -> Option<Value> {
+        use Value::{Bool, Int};
+        match (self, lhs, rhs) {
+            (BinOp::Add, Int(a), Int(b)) => a.checked_add(b).map(Int),
+            (BinOp::Sub, Int(a), Int(b)) => a.checked_sub(b).map(Int),
+            (BinOp::Mul, Int(a), Int(b)) => a.checked_mul(b).map(Int),
+            (BinOp::Div, Int(a), Int(b)) => a.checked_div(b).map(Int),
+            (BinOp::Lt, Int(a), Int(b)) => Some(Bool(a < b)),
+            (BinOp::Le, Int(a), Int(b)) => Some(Bool(a <= b)),
+            (BinOp::Gt, Int(a), Int(b)) => Some(Bool(a > b)),
+            (BinOp::Ge, Int(a), Int(b)) => Some(Bool(a >= b)),
+            (BinOp::Eq, a, b) => Some(Bool(a == b)),
+            (BinOp::And, Bool(a), Bool(b)) => Some(Bool(a && b)),
+            (BinOp::Or, Bool(a), Bool(b)) => Some(Bool(a || b)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The error raised when the operator fails in an expression
+    pub fn failure(&self, e: &Expr) -> String {
+        match self {
+            BinOp::Div => format!("division by zero in {}", e),
+            _ => format!("overflow in {}", e),
+        }
+    }
+
+    /// Types of the arguments and of the result
+    fn signature(&self) -> (Type, Type) {
+        match self {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => (Type::Int, Type::Int),
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => (Type::Int, Type::Bool),
+            BinOp::And | BinOp::Or => (Type::Bool, Type::Bool),
+            BinOp::Eq => unreachable!(),
+        }
+    }
+}
+
+impl Expr {
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        match sexpr {
+            Sexpr::Num(n, _) => Ok(Expr::Int(n)),
+            Sexpr::Sym(s, _) if s == "true" => Ok(Expr::Bool(true)),
+            Sexpr::Sym(s, _) if s == "false" => Ok(Expr::Bool(false)),
+            Sexpr::Sym(s, _) => Ok(Expr::Var(s)),
+            Sexpr::Str(_, _) => Err(sexpr.error("an expression")),
+            Sexpr::List(_, _) => {
+                let mut buff = sexpr.into_buff("an expression")?;
+                let pos = buff.position();
+                let op = buff.expect_symb()?;
+                if op == "not" {
+                    let e = buff.expect_convert(Self::from_sexpr)?;
+                    buff.expect_end()?;
+                    return Ok(Expr::Not(Box::new(e)));
+                }
+                let Some(binop) = BinOp::from_symb(&op) else {
+                    return Err(ParseError::new(pos, "an operator", op));
+                };
+                let lhs = buff.expect_convert(Self::from_sexpr)?;
+                let args = if binop.is_associative() {
+                    buff.convert_list(Self::from_sexpr)?
+                } else {
+                    let rhs = buff.expect_convert(Self::from_sexpr)?;
+                    buff.expect_end()?;
+                    vec![rhs]
+                };
+                Ok(args.into_iter().fold(lhs, |lhs, rhs| {
+                    Expr::Bin(binop, Box::new(lhs), Box::new(rhs))
+                }))
+            }
+        }
+    }
+
+    /// Type of the expression, given the domains of the variables
+    pub fn typecheck(&self, vars: &HashMap<String, Domain>) -> Result<Type, String> {
+        match self {
+            Expr::Int(_) => Ok(Type::Int),
+            Expr::Bool(_) => Ok(Type::Bool),
+            Expr::Var(x) => vars
+                .get(x)
+                .map(Domain::typ)
+                .ok_or_else(|| format!("use of undeclared variable {}", x)),
+            Expr::Not(e) => {
+                e.expect_type(Type::Bool, vars)?;
+                Ok(Type::Bool)
+            }
+            Expr::Bin(BinOp::Eq, lhs, rhs) => {
+                let typ = lhs.typecheck(vars)?;
+                rhs.expect_type(typ, vars)?;
+                Ok(Type::Bool)
+            }
+            Expr::Bin(op, lhs, rhs) => {
+                let (args, res) = op.signature();
+                lhs.expect_type(args, vars)?;
+                rhs.expect_type(args, vars)?;
+                Ok(res)
+            }
+        }
+    }
+
+    pub fn expect_type(&self, typ: Type, vars: &HashMap<String, Domain>) -> Result<(), String> {
+        let found = self.typecheck(vars)?;
+        if found == typ {
+            Ok(())
+        } else {
+            Err(format!(
+                "expression {} has type {:?} instead of {:?}",
+                self, found, typ
+            ))
+        }
+    }
+
+    /// Evaluate a well-typed expression in a valuation
+    pub fn eval(&self, index: &HashMap<String, usize>, val: &[Value]) -> Result<Value, String> {
+        match self {
+            Expr::Int(n) => Ok(Value::Int(*n)),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Var(x) => Ok(val[index[x]]),
+            Expr::Not(e) => match e.eval(index, val)? {
+                Value::Bool(b) => Ok(Value::Bool(!b)),
+                Value::Int(_) => unreachable!(),
+            },
+            Expr::Bin(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(index, val)?, rhs.eval(index, val)?);
+                op.apply(lhs, rhs).ok_or_else(|| op.failure(self))
+            }
+        }
+    }
+}
+
+impl VarDecl {
+    /// Read the declaration of an integer variable: `<name> <min> <max> <init>`
+    pub fn parse_int(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let name = buff.expect_symb()?;
+        let min = buff.expect_int()?;
+        let max = buff.expect_int()?;
+        let init = buff.expect_int()?;
+        Ok(VarDecl {
+            name,
+            domain: Domain::Int(min, max),
+            init: Value::Int(init),
+        })
+    }
+
+    /// Read the declaration of a boolean variable: `<name> <init>`
+    pub fn parse_bool(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let name = buff.expect_symb()?;
+        let init = buff.expect_next("true or false")?;
+        let init = match init.clone().get_symb_opt().as_deref() {
+            Some("true") => true,
+            Some("false") => false,
+            _ => return Err(init.error("true or false")),
+        };
+        Ok(VarDecl {
+            name,
+            domain: Domain::Bool,
+            init: Value::Bool(init),
+        })
+    }
+}
+
+impl Rule {
+    fn update_from_sexpr(sexpr: Sexpr) -> Result<(String, Expr), ParseError> {
+        let mut buff = sexpr.into_buff("an assignment")?;
+        let pos = buff.position();
+        let set = buff.expect_symb()?;
+        if set != "set" {
+            return Err(ParseError::new(pos, "set", set));
+        }
+        let x = buff.expect_symb()?;
+        let e = buff.expect_convert(Expr::from_sexpr)?;
+        buff.expect_end()?;
+        Ok((x, e))
+    }
+
+    /// Read a guarded command: `<action> <guard> (set <var> <expr>)...`
+    pub fn parse(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let action = buff.expect_symb()?;
+        let guard = buff.expect_convert(Expr::from_sexpr)?;
+        let mut updates = vec![];
+        while !buff.is_empty() {
+            updates.push(buff.expect_convert(Self::update_from_sexpr)?);
+        }
+        Ok(Rule {
+            action,
+            guard,
+            updates,
+        })
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::Eq => "=",
+            BinOp::And => "and",
+            BinOp::Or => "or",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Int(n) => write!(f, "{}", n),
+            Expr::Bool(b) => write!(f, "{}", b),
+            Expr::Var(x) => write!(f, "{}", x),
+            Expr::Not(e) => write!(f, "(not {})", e),
+            Expr::Bin(op, lhs, rhs) => write!(f, "({} {} {})", op, lhs, rhs),
+        }
+    }
+}
+
+impl Display for VarDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.domain {
+            Domain::Bool => write!(f, "(bool {} {})", self.name, self.init),
+            Domain::Int(min, max) => {
+                write!(f, "(int {} {} {} {})", self.name, min, max, self.init)
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(rule {} {}", self.action, self.guard)?;
+        for (x, e) in self.updates.iter() {
+            write!(f, " (set {} {})", x, e)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expr(s: &str) -> Expr {
+        Expr::from_sexpr(s.parse::<Sexpr>().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_1() {
+        let vars = HashMap::from([("x".to_string(), Domain::Int(0, 3))]);
+        assert_eq!(expr("(< x (+ x 1 2))").typecheck(&vars), Ok(Type::Bool));
+        assert!(expr("(and x true)").typecheck(&vars).is_err());
+        assert!(expr("(= y 1)").typecheck(&vars).is_err());
+    }
+
+    #[test]
+    fn test_2() {
+        let index = HashMap::from([("x".to_string(), 0), ("b".to_string(), 1)]);
+        let val = [Value::Int(2), Value::Bool(false)];
+        assert_eq!(
+            expr("(- (* x 3) (/ 7 x))").eval(&index, &val),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            expr("(or b (>= x 2))").eval(&index, &val),
+            Ok(Value::Bool(true))
+        );
+        assert!(expr("(/ x 0)").eval(&index, &val).is_err());
+        assert_eq!(
+            expr("(- -1 (* x -3))").eval(&index, &val),
+            Ok(Value::Int(5))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// Compute the strongly connected components of a graph
+/// using Tarjan's algorithm.
+///
+/// The graph is given by its set of nodes and a function
+/// returning the successors of a node. Components are returned
+/// in reverse topological order: every edge leaving a component
+/// goes to a component that appears before it.
+pub fn sccs<N, F>(nodes: N, succ: F) -> Vec<Vec<u32>>
+where
+    N: IntoIterator<Item = u32>,
+    F: Fn(u32) -> Vec<u32>,
+{
+    let mut index = HashMap::<u32, usize>::new();
+    let mut low = HashMap::<u32, usize>::new();
+    let mut on_stack = HashMap::<u32, bool>::new();
+    let mut stack = vec![];
+    let mut components = vec![];
+
+    for root in nodes {
+        if index.contains_key(&root) {
+            continue;
+        }
+        // Explicit call stack: a node and the successors left to visit
+        let mut calls = vec![(root, succ(root).into_iter())];
+        index.insert(root, index.len());
+        low.insert(root, index[&root]);
+        stack.push(root);
+        on_stack.insert(root, true);
+        while let Some((x, todo)) = calls.last_mut() {
+            let x = *x;
+            if let Some(y) = todo.next() {
+                if !index.contains_key(&y) {
+                    index.insert(y, index.len());
+                    low.insert(y, index[&y]);
+                    stack.push(y);
+                    on_stack.insert(y, true);
+                    calls.push((y, succ(y).into_iter()));
+                } else if on_stack[&y] {
+                    low.insert(x, low[&x].min(index[&y]));
+                }
+            } else {
+                calls.pop();
+                if let Some((parent, _)) = calls.last() {
+                    low.insert(*parent, low[parent].min(low[&x]));
+                }
+                if low[&x] == index[&x] {
+                    let mut component = vec![];
+                    loop {
+                        let y = stack.pop().unwrap();
+                        on_stack.insert(y, false);
+                        component.push(y);
+                        if y == x {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn normalize(mut components: Vec<Vec<u32>>) -> Vec<Vec<u32>> {
+        components.iter_mut().for_each(|c| c.sort());
+        components
+    }
+
+    #[test]
+    fn test_1() {
+        let edges = |x: u32| match x {
+            1 => vec![2],
+            2 => vec![3],
+            3 => vec![1, 4],
+            _ => vec![],
+        };
+        assert_eq!(
+            normalize(sccs(vec![1, 2, 3, 4], edges)),
+            vec![vec![4], vec![1, 2, 3]]
+        )
+    }
+
+    #[test]
+    fn test_2() {
+        let edges = |x: u32| match x {
+            1 => vec![1],
+            _ => vec![],
+        };
+        assert_eq!(normalize(sccs(vec![1, 2], edges)), vec![vec![1], vec![2]])
+    }
+}
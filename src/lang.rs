@@ -1,181 +1,664 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    fmt::Display,
     str::FromStr,
 };
 
-use crate::{buff::Buff, mu::Mu, sexpr::Sexpr, ts::Ts};
+use crate::{
+    buff::{Buff, ParseError},
+    dense::Transitions,
+    fair::Fairness,
+    gcl::{Domain, Expr, Rule, Type, Value, VarDecl},
+    sexpr::Sexpr,
+    spec::Spec,
+    ts::Ts,
+};
 
-#[derive(Debug, PartialEq, Eq)]
+#[cfg(feature = "symbolic")]
+use crate::symbolic::Symbolic;
+
+/// A state of a program, given by its number or by its name
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    Id(u32),
+    Name(String),
+}
+
+impl State {
+    fn is_state(s: &Sexpr) -> bool {
+        s.is_num() || s.is_symb()
+    }
+
+    fn from_sexpr(s: Sexpr) -> Self {
+        match s {
+            Sexpr::Num(_, _) => State::Id(s.get_num()),
+            _ => State::Name(s.get_symb()),
+        }
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Id(n) => write!(f, "{}", n),
+            State::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Instr {
     SetProps(Vec<String>),
     SetActions(Vec<String>),
-    SetInit(Vec<u32>),
-    SetSpec(Mu<String, String>),
-    Label(u32, Vec<String>),
-    Trans(u32, String, u32),
-    Loop(u32, String),
+    SetStates(Vec<State>),
+    SetInit(Vec<State>),
+    SetSpec(Spec<String, String>),
+    Fair(Fairness<String, String>),
+    Label(State, Vec<String>),
+    Trans(State, String, State),
+    Loop(State, String),
+    DeclVar(VarDecl),
+    Rule(Rule),
+    Prop(String, Expr),
+    Process(String, Vec<Instr>),
+    System(Vec<String>, Vec<String>),
 }
 
 impl Instr {
-    fn expect_symb_list(buff: &mut Buff<Sexpr>) -> Option<Vec<String>> {
-        buff.convert_while(Sexpr::is_symb, Sexpr::get_symb)
+    fn expect_symb_list(buff: &mut Buff<Sexpr>) -> Result<Vec<String>, ParseError> {
+        buff.convert_while(Sexpr::is_symb, Sexpr::get_symb, "a symbol")
+    }
+
+    fn expect_state(buff: &mut Buff<Sexpr>) -> Result<State, ParseError> {
+        buff.expect_cond(State::is_state, "a state")
+            .map(State::from_sexpr)
     }
 
-    fn expect_num_list(buff: &mut Buff<Sexpr>) -> Option<Vec<u32>> {
-        buff.convert_while(Sexpr::is_num, Sexpr::get_num)
+    fn expect_state_list(buff: &mut Buff<Sexpr>) -> Result<Vec<State>, ParseError> {
+        buff.convert_while(State::is_state, State::from_sexpr, "a state")
     }
 
-    fn from_sexpr(s: Sexpr) -> Option<Instr> {
-        if !s.is_list() {
-            return None;
+    /// States occurring in the instruction
+    fn states(&self) -> Vec<&State> {
+        match self {
+            Instr::SetStates(states) | Instr::SetInit(states) => states.iter().collect(),
+            Instr::Label(s, _) | Instr::Loop(s, _) => vec![s],
+            Instr::Trans(s1, _, s2) => vec![s1, s2],
+            _ => vec![],
         }
-        let list = s.get_list();
-        let mut buff = Buff::new(list);
-        let cmd = buff.expect_cond(Sexpr::is_symb)?.get_symb();
-        if cmd == *"label" {
-            let n = buff.expect_cond(Sexpr::is_num)?.get_num();
-            let label = Self::expect_symb_list(&mut buff)?;
-            Some(Instr::Label(n, label))
-        } else if cmd == *"props" {
-            let label = Self::expect_symb_list(&mut buff)?;
-            Some(Instr::SetProps(label))
-        } else if cmd == *"init" {
-            let init = Self::expect_num_list(&mut buff)?;
-            Some(Instr::SetInit(init))
-        } else if cmd == *"actions" {
-            let actions = Self::expect_symb_list(&mut buff)?;
-            Some(Instr::SetActions(actions))
-        } else if cmd == *"trans" {
-            let state1 = buff.expect_cond(Sexpr::is_num)?.get_num();
-            let action = buff.expect_cond(Sexpr::is_symb)?.get_symb();
-            let state2 = buff.expect_cond(Sexpr::is_num)?.get_num();
-            Some(Instr::Trans(state1, action, state2))
-        } else if cmd == *"loop" {
-            let state1 = buff.expect_cond(Sexpr::is_num)?.get_num();
-            let action = buff.expect_cond(Sexpr::is_symb)?.get_symb();
-            Some(Instr::Loop(state1, action))
-        } else if cmd == *"spec" {
-            let spec = buff.next()?;
-            buff.expect_end()?;
-            Some(Instr::SetSpec(Mu::from_sexpr(spec)?))
+    }
+
+    /// Instructions of guarded-command programs
+    fn is_guarded(&self) -> bool {
+        matches!(self, Instr::DeclVar(_) | Instr::Rule(_) | Instr::Prop(_, _))
+    }
+
+    /// Read the components of a system: `(par <process>...) [(sync <action>...)]`
+    fn parse_system(buff: &mut Buff<Sexpr>) -> Result<Instr, ParseError> {
+        let mut par = buff
+            .expect_next("a parallel composition")?
+            .into_buff("a parallel composition")?;
+        let pos = par.position();
+        let op = par.expect_symb()?;
+        if op != "par" {
+            return Err(ParseError::new(pos, "par", op));
+        }
+        let processes = Self::expect_symb_list(&mut par)?;
+        par.expect_end()?;
+        let sync = if buff.is_empty() {
+            vec![]
         } else {
-            None
+            let mut sync = buff.expect_next("sync")?.into_buff("sync")?;
+            let pos = sync.position();
+            let op = sync.expect_symb()?;
+            if op != "sync" {
+                return Err(ParseError::new(pos, "sync", op));
+            }
+            let actions = Self::expect_symb_list(&mut sync)?;
+            sync.expect_end()?;
+            actions
+        };
+        Ok(Instr::System(processes, sync))
+    }
+
+    /// Instructions describing the state space explicitly
+    fn is_explicit(&self) -> bool {
+        matches!(
+            self,
+            Instr::SetStates(_)
+                | Instr::SetInit(_)
+                | Instr::Label(_, _)
+                | Instr::Trans(_, _, _)
+                | Instr::Loop(_, _)
+        )
+    }
+
+    /// Add the propositions and actions declared by the instruction,
+    /// or by the instructions of a process
+    fn declarations(&self, props: &mut HashSet<String>, actions: &mut HashSet<String>) {
+        match self {
+            Instr::SetProps(declared) => props.extend(declared.iter().cloned()),
+            Instr::Prop(p, _) => {
+                props.insert(p.clone());
+            }
+            Instr::SetActions(declared) => actions.extend(declared.iter().cloned()),
+            Instr::Rule(rule) => {
+                actions.insert(rule.action.clone());
+            }
+            Instr::Process(_, instrs) => {
+                for instr in instrs {
+                    instr.declarations(props, actions);
+                }
+            }
+            _ => (),
         }
     }
 
-    fn parse(buff: &mut Buff<char>) -> Option<Self> {
+    fn from_sexpr(s: Sexpr) -> Result<Instr, ParseError> {
+        let mut buff = s.into_buff("an instruction")?;
+        let pos = buff.position();
+        let cmd = buff.expect_symb()?;
+        let instr = match cmd.as_str() {
+            "label" => {
+                let state = Self::expect_state(&mut buff)?;
+                let label = Self::expect_symb_list(&mut buff)?;
+                Instr::Label(state, label)
+            }
+            "props" => Instr::SetProps(Self::expect_symb_list(&mut buff)?),
+            "states" => Instr::SetStates(Self::expect_state_list(&mut buff)?),
+            "init" => Instr::SetInit(Self::expect_state_list(&mut buff)?),
+            "actions" => Instr::SetActions(Self::expect_symb_list(&mut buff)?),
+            "trans" => {
+                let state1 = Self::expect_state(&mut buff)?;
+                let action = buff.expect_symb()?;
+                let state2 = Self::expect_state(&mut buff)?;
+                Instr::Trans(state1, action, state2)
+            }
+            "loop" => {
+                let state1 = Self::expect_state(&mut buff)?;
+                let action = buff.expect_symb()?;
+                Instr::Loop(state1, action)
+            }
+            "spec" => Instr::SetSpec(buff.expect_convert(Spec::from_sexpr)?),
+            "fair" => Instr::Fair(Fairness::parse(&mut buff)?),
+            "int" => Instr::DeclVar(VarDecl::parse_int(&mut buff)?),
+            "bool" => Instr::DeclVar(VarDecl::parse_bool(&mut buff)?),
+            "rule" => Instr::Rule(Rule::parse(&mut buff)?),
+            "prop" => {
+                let prop = buff.expect_symb()?;
+                let e = buff.expect_convert(Expr::from_sexpr)?;
+                Instr::Prop(prop, e)
+            }
+            "process" => {
+                let name = buff.expect_symb()?;
+                Instr::Process(name, buff.convert_list(Self::from_sexpr)?)
+            }
+            "system" => Self::parse_system(&mut buff)?,
+            _ => {
+                return Err(ParseError::new(
+                    pos,
+                    "one of label, props, states, init, actions, trans, loop, spec, fair, int, bool, rule, prop, process, system",
+                    cmd,
+                ))
+            }
+        };
+        buff.expect_end()?;
+        Ok(instr)
+    }
+
+    fn parse(buff: &mut Buff<char>) -> Result<Self, ParseError> {
         Sexpr::parse(buff).and_then(Instr::from_sexpr)
     }
 }
 
+impl Display for Instr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instr::SetProps(props) => write!(f, "(props {})", props.join(" ")),
+            Instr::SetActions(actions) => write!(f, "(actions {})", actions.join(" ")),
+            Instr::SetStates(states) => {
+                let states = states.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                write!(f, "(states {})", states.join(" "))
+            }
+            Instr::SetInit(init) => {
+                let init = init.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                write!(f, "(init {})", init.join(" "))
+            }
+            Instr::SetSpec(spec) => write!(f, "(spec {})", spec),
+            Instr::Fair(fairness) => write!(f, "(fair {})", fairness),
+            Instr::Label(s, label) => write!(f, "(label {} {})", s, label.join(" ")),
+            Instr::Trans(s1, a, s2) => write!(f, "(trans {} {} {})", s1, a, s2),
+            Instr::Loop(s, a) => write!(f, "(loop {} {})", s, a),
+            Instr::DeclVar(decl) => write!(f, "{}", decl),
+            Instr::Rule(rule) => write!(f, "{}", rule),
+            Instr::Prop(p, e) => write!(f, "(prop {} {})", p, e),
+            Instr::Process(name, instrs) => {
+                let instrs = instrs.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+                write!(f, "(process {} {})", name, instrs.join(" "))
+            }
+            Instr::System(processes, sync) if sync.is_empty() => {
+                write!(f, "(system (par {}))", processes.join(" "))
+            }
+            Instr::System(processes, sync) => write!(
+                f,
+                "(system (par {}) (sync {}))",
+                processes.join(" "),
+                sync.join(" ")
+            ),
+        }
+    }
+}
+
+/// Errors raised while compiling a program.
+/// Each error records the offending instruction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileError {
+    DuplicateProps(Instr),
+    DuplicateActions(Instr),
+    DuplicateStates(Instr),
+    DuplicateInit(Instr),
+    DuplicateLabel(State, Instr),
+    DuplicateTransition(Instr),
+    UndeclaredProposition(String, Instr),
+    UndeclaredAction(String, Instr),
+    NonMonotone(String, Instr),
+    DuplicateVariable(String, Instr),
+    IllTyped(String, Instr),
+    EvalError(String, Instr),
+    MixedProgram(Instr),
+    DuplicateProcess(String, Instr),
+    DuplicateSystem(Instr),
+    UndeclaredProcess(String, Instr),
+    /// Errors in the program of a process
+    InProcess(String, Vec<CompileError>),
+}
+
+impl CompileError {
+    /// The offending instruction
+    pub fn instr(&self) -> &Instr {
+        match self {
+            CompileError::InProcess(_, errors) => errors[0].instr(),
+            CompileError::DuplicateProps(instr)
+            | CompileError::DuplicateActions(instr)
+            | CompileError::DuplicateStates(instr)
+            | CompileError::DuplicateInit(instr)
+            | CompileError::DuplicateLabel(_, instr)
+            | CompileError::DuplicateTransition(instr)
+            | CompileError::UndeclaredProposition(_, instr)
+            | CompileError::UndeclaredAction(_, instr)
+            | CompileError::NonMonotone(_, instr)
+            | CompileError::DuplicateVariable(_, instr)
+            | CompileError::IllTyped(_, instr)
+            | CompileError::EvalError(_, instr)
+            | CompileError::MixedProgram(instr)
+            | CompileError::DuplicateProcess(_, instr)
+            | CompileError::DuplicateSystem(instr)
+            | CompileError::UndeclaredProcess(_, instr) => instr,
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let CompileError::InProcess(name, errors) = self {
+            let errors = errors
+                .iter()
+                .map(|err| format!("In process {}: {}", name, err));
+            return write!(f, "{}", errors.collect::<Vec<_>>().join("\n"));
+        }
+        write!(f, "Ill-formed program: ")?;
+        match self {
+            CompileError::DuplicateProps(_) => write!(f, "the proposition set is declared twice"),
+            CompileError::DuplicateActions(_) => write!(f, "the action set is declared twice"),
+            CompileError::DuplicateStates(_) => write!(f, "the state set is declared twice"),
+            CompileError::DuplicateInit(_) => write!(f, "the initial states are declared twice"),
+            CompileError::DuplicateLabel(s, _) => {
+                write!(f, "the label for states {} is declared twice", s)
+            }
+            CompileError::DuplicateTransition(_) => write!(f, "the transition is declared twice"),
+            CompileError::UndeclaredProposition(p, _) => {
+                write!(f, "use of undeclared proposition {}", p)
+            }
+            CompileError::UndeclaredAction(a, _) => write!(f, "use of undeclared action {}", a),
+            CompileError::NonMonotone(err, _)
+            | CompileError::IllTyped(err, _)
+            | CompileError::EvalError(err, _) => write!(f, "{}", err),
+            CompileError::DuplicateVariable(x, _) => {
+                write!(f, "the variable {} is declared twice", x)
+            }
+            CompileError::MixedProgram(_) => write!(
+                f,
+                "explicit states, variables and rules, and systems of processes cannot be mixed"
+            ),
+            CompileError::DuplicateProcess(p, _) => {
+                write!(f, "the process {} is declared twice", p)
+            }
+            CompileError::DuplicateSystem(_) => write!(f, "the system is declared twice"),
+            CompileError::UndeclaredProcess(p, _) => write!(f, "use of undeclared process {}", p),
+            CompileError::InProcess(_, _) => unreachable!(),
+        }?;
+        write!(f, " in {}", self.instr())
+    }
+}
+
 pub struct ProgEnv {
     props: HashSet<String>,
     actions: HashSet<String>,
+    ids: HashMap<String, u32>,
+    declared_states: bool,
     states: HashSet<u32>,
-    spec: Vec<Mu<String, String>>,
+    spec: Vec<Spec<String, String>>,
+    fairness: Vec<Fairness<String, String>>,
     initial: HashSet<u32>,
     labels: HashMap<u32, HashSet<String>>,
-    transitions: HashMap<u32, HashMap<String, u32>>,
+    transitions: HashMap<u32, HashMap<String, HashSet<u32>>>,
+    names: HashMap<u32, String>,
+    /// Propositions and actions declared anywhere in the program (or in its
+    /// processes), which the specification may refer to
+    declared_props: HashSet<String>,
+    declared_actions: HashSet<String>,
+    guarded: bool,
+    vars: Vec<VarDecl>,
+    domains: HashMap<String, Domain>,
+    rules: Vec<Rule>,
+    definitions: Vec<(String, Expr)>,
+    composed: bool,
+    processes: HashMap<String, Ts<String, String>>,
+    system: Option<(Vec<String>, Vec<String>)>,
 }
 
 impl ProgEnv {
-    pub fn exec(&mut self, instr: Instr) -> Result<(), String> {
-        match instr {
+    /// Number of a state, named states are interned beforehand
+    fn id(&self, s: &State) -> u32 {
+        match s {
+            State::Id(n) => *n,
+            State::Name(name) => self.ids[name],
+        }
+    }
+
+    pub fn exec(&mut self, instr: Instr) -> Result<(), CompileError> {
+        let mixed = (self.guarded && instr.is_explicit())
+            || (self.composed && (instr.is_explicit() || instr.is_guarded()));
+        if mixed {
+            return Err(CompileError::MixedProgram(instr));
+        }
+        match &instr {
             Instr::SetProps(props) => {
                 if self.props.is_empty() {
-                    for prop in props {
-                        self.props.insert(prop);
-                    }
+                    self.props.extend(props.iter().cloned());
                     Ok(())
                 } else {
-                    Err("Ill-formed program: the proposition set is declared twice".to_string())
+                    Err(CompileError::DuplicateProps(instr))
                 }
             }
             Instr::SetActions(actions) => {
                 if self.actions.is_empty() {
-                    for action in actions {
-                        self.actions.insert(action);
-                    }
+                    self.actions.extend(actions.iter().cloned());
                     Ok(())
                 } else {
-                    Err("Ill-formed program: the action set is declared twice".to_string())
+                    Err(CompileError::DuplicateActions(instr))
+                }
+            }
+            Instr::SetStates(states) => {
+                if !self.declared_states {
+                    self.declared_states = true;
+                    let states = states.iter().map(|s| self.id(s)).collect::<Vec<_>>();
+                    self.states.extend(states);
+                    Ok(())
+                } else {
+                    Err(CompileError::DuplicateStates(instr))
                 }
             }
             Instr::SetInit(initial) => {
                 if self.initial.is_empty() {
-                    for init in initial {
-                        self.initial.insert(init);
-                    }
+                    let initial = initial.iter().map(|s| self.id(s)).collect::<Vec<_>>();
+                    self.initial.extend(initial);
                     Ok(())
                 } else {
-                    Err("Ill-formed program: the initial states are declared twice".to_string())
+                    Err(CompileError::DuplicateInit(instr))
                 }
             }
-            Instr::SetSpec(s) => Ok(self.spec.push(s)),
+            Instr::SetSpec(s) => {
+                if let Spec::Mu(mu) = s {
+                    if let Err(err) = mu.check_monotone() {
+                        return Err(CompileError::NonMonotone(err, instr));
+                    }
+                }
+                let (props, actions) = s.signature();
+                if let Some(p) = props.into_iter().find(|p| !self.declared_props.contains(p)) {
+                    return Err(CompileError::UndeclaredProposition(p, instr));
+                }
+                if let Some(a) = actions
+                    .into_iter()
+                    .find(|a| !self.declared_actions.contains(a))
+                {
+                    return Err(CompileError::UndeclaredAction(a, instr));
+                }
+                self.spec.push(s.clone());
+                Ok(())
+            }
+            Instr::Fair(fairness) => {
+                self.fairness.push(fairness.clone());
+                Ok(())
+            }
             Instr::Label(s, label) => {
+                let id = self.id(s);
                 if let Some(prop) = label.iter().find(|p| !self.props.contains(*p)) {
-                    Err(format!(
-                        "Ill-formed program: use of undeclared proposition {}",
-                        prop
-                    ))
-                } else if self.labels.get(&s).is_some() {
-                    Err(format!(
-                        "Ill-formed program: the label for states {} is declared twice",
-                        s
-                    ))
-                } else {
-                    self.labels.insert(s, HashSet::from_iter(label.into_iter()));
+                    Err(CompileError::UndeclaredProposition(prop.clone(), instr))
+                } else if let Entry::Vacant(e) = self.labels.entry(id) {
+                    e.insert(label.iter().cloned().collect());
                     Ok(())
+                } else {
+                    Err(CompileError::DuplicateLabel(s.clone(), instr))
                 }
             }
-
             Instr::Trans(s1, a, s2) => {
+                let (s1, s2) = (self.id(s1), self.id(s2));
                 self.states.insert(s1);
                 self.states.insert(s2);
-                if !self.actions.contains(&a) {
-                    Err(format!(
-                        "Ill-formed program: use of undeclared action {}",
-                        a
-                    ))
-                } else if let Some(h) = self.transitions.get_mut(&s1) {
-                    if h.get(&a).is_some() {
-                        Err(format!(
-                            "Ill-formed program: the {}-transition for states {} is declared twice",
-                            a, s1
-                        ))
-                    } else {
-                        h.insert(a, s2);
-                        Ok(())
+                self.add_transition(s1, a.clone(), s2, instr)
+            }
+            Instr::Loop(s, a) => {
+                let s = self.id(s);
+                self.states.insert(s);
+                self.add_transition(s, a.clone(), s, instr)
+            }
+            Instr::DeclVar(decl) => {
+                if self.domains.contains_key(&decl.name) {
+                    return Err(CompileError::DuplicateVariable(decl.name.clone(), instr));
+                }
+                if !decl.domain.contains(decl.init) {
+                    return Err(CompileError::IllTyped(
+                        format!("the initial value of {} is out of its bounds", decl.name),
+                        instr,
+                    ));
+                }
+                self.domains.insert(decl.name.clone(), decl.domain);
+                self.vars.push(decl.clone());
+                Ok(())
+            }
+            Instr::Rule(rule) => {
+                let check = || {
+                    rule.guard.expect_type(Type::Bool, &self.domains)?;
+                    for (x, e) in rule.updates.iter() {
+                        let domain = self
+                            .domains
+                            .get(x)
+                            .ok_or_else(|| format!("use of undeclared variable {}", x))?;
+                        e.expect_type(domain.typ(), &self.domains)?;
                     }
-                } else {
-                    self.transitions.insert(s1, HashMap::from_iter([(a, s2)]));
                     Ok(())
+                };
+                match check() {
+                    Ok(()) => {
+                        self.rules.push(rule.clone());
+                        Ok(())
+                    }
+                    Err(err) => Err(CompileError::IllTyped(err, instr)),
                 }
             }
-            Instr::Loop(s, a) => {
-                self.states.insert(s);
-                if !self.actions.contains(&a) {
-                    Err(format!(
-                        "Ill-formed program: use of undeclared action {}",
-                        a
-                    ))
-                } else if let Some(h) = self.transitions.get_mut(&s) {
-                    if h.get(&a).is_some() {
-                        Err(format!(
-                            "Ill-formed program: the {}-transition for states {} is declared twice",
-                            a, s
-                        ))
-                    } else {
-                        h.insert(a, s);
+            Instr::Prop(p, e) => match e.expect_type(Type::Bool, &self.domains) {
+                Ok(()) => {
+                    self.definitions.push((p.clone(), e.clone()));
+                    Ok(())
+                }
+                Err(err) => Err(CompileError::IllTyped(err, instr)),
+            },
+            Instr::Process(name, instrs) => {
+                if self.processes.contains_key(name) {
+                    return Err(CompileError::DuplicateProcess(name.clone(), instr));
+                }
+                let prog = Prog {
+                    instructions: instrs.clone(),
+                };
+                match prog.compile() {
+                    Ok(ts) => {
+                        self.processes.insert(name.clone(), ts);
                         Ok(())
                     }
+                    Err(errors) => Err(CompileError::InProcess(name.clone(), errors)),
+                }
+            }
+            Instr::System(processes, sync) => {
+                if self.system.is_some() {
+                    Err(CompileError::DuplicateSystem(instr))
+                } else if let Some(p) = processes.iter().find(|p| !self.processes.contains_key(*p))
+                {
+                    Err(CompileError::UndeclaredProcess(p.clone(), instr))
                 } else {
-                    self.transitions.insert(s, HashMap::from_iter([(a, s)]));
+                    self.system = Some((processes.clone(), sync.clone()));
                     Ok(())
                 }
             }
         }
     }
+
+    /// Parallel composition of the processes of the system
+    fn compose(&self, processes: &[String], sync: &[String]) -> Ts<String, String> {
+        let components = processes
+            .iter()
+            .map(|p| &self.processes[p])
+            .collect::<Vec<_>>();
+        let mut ts = Ts::compose(&components, sync);
+        ts.spec.extend(self.spec.iter().cloned());
+        ts.fairness.extend(self.fairness.iter().cloned());
+        ts
+    }
+
+    /// Enumerate the valuations of the variables reachable from the
+    /// initial one through the rules.
+    /// States are numbered in breadth-first order, starting from 1
+    fn explore(&mut self) -> Result<(), CompileError> {
+        let index = self
+            .vars
+            .iter()
+            .enumerate()
+            .map(|(i, decl)| (decl.name.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let name = |val: &[Value]| {
+            let vars = self
+                .vars
+                .iter()
+                .zip(val)
+                .map(|(decl, v)| format!("{}={}", decl.name, v))
+                .collect::<Vec<_>>();
+            format!("({})", vars.join(", "))
+        };
+        let init = self.vars.iter().map(|decl| decl.init).collect::<Vec<_>>();
+        let mut ids = HashMap::from([(init.clone(), 1)]);
+        let mut queue = VecDeque::from([init]);
+        let mut names = HashMap::new();
+        let mut labels = HashMap::new();
+        let mut transitions = HashMap::<u32, HashMap<String, HashSet<u32>>>::new();
+        while let Some(val) = queue.pop_front() {
+            let s = ids[&val];
+            names.insert(s, name(&val));
+            let mut label = HashSet::new();
+            for (p, e) in self.definitions.iter() {
+                match e.eval(&index, &val) {
+                    Ok(Value::Bool(true)) => {
+                        label.insert(p.clone());
+                    }
+                    Ok(_) => (),
+                    Err(err) => {
+                        return Err(CompileError::EvalError(
+                            format!("{} in state {}", err, name(&val)),
+                            Instr::Prop(p.clone(), e.clone()),
+                        ))
+                    }
+                }
+            }
+            if !label.is_empty() {
+                labels.insert(s, label);
+            }
+            for rule in self.rules.iter() {
+                let error = |err| {
+                    CompileError::EvalError(
+                        format!("{} in state {}", err, name(&val)),
+                        Instr::Rule(rule.clone()),
+                    )
+                };
+                if rule.guard.eval(&index, &val).map_err(error)? != Value::Bool(true) {
+                    continue;
+                }
+                let mut next = val.clone();
+                for (x, e) in rule.updates.iter() {
+                    let v = e.eval(&index, &val).map_err(error)?;
+                    if !self.domains[x].contains(v) {
+                        return Err(error(format!(
+                            "the value {} of {} is out of its bounds",
+                            v, x
+                        )));
+                    }
+                    next[index[x]] = v;
+                }
+                let t = match ids.get(&next) {
+                    Some(t) => *t,
+                    None => {
+                        let t = ids.len() as u32 + 1;
+                        ids.insert(next.clone(), t);
+                        queue.push_back(next);
+                        t
+                    }
+                };
+                transitions
+                    .entry(s)
+                    .or_default()
+                    .entry(rule.action.clone())
+                    .or_default()
+                    .insert(t);
+            }
+        }
+        self.states = ids.into_values().collect();
+        self.initial = HashSet::from([1]);
+        self.labels = labels;
+        self.transitions = transitions;
+        self.names = names;
+        Ok(())
+    }
+
+    fn add_transition(
+        &mut self,
+        s1: u32,
+        a: String,
+        s2: u32,
+        instr: Instr,
+    ) -> Result<(), CompileError> {
+        if !self.actions.contains(&a) {
+            Err(CompileError::UndeclaredAction(a, instr))
+        } else if self
+            .transitions
+            .entry(s1)
+            .or_default()
+            .entry(a)
+            .or_default()
+            .insert(s2)
+        {
+            Ok(())
+        } else {
+            Err(CompileError::DuplicateTransition(instr))
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -184,41 +667,144 @@ pub struct Prog {
 }
 
 impl Prog {
-    pub fn compile(self) -> Result<Ts<String, String>, String> {
+    /// Compile the program into a transition system.
+    /// All the ill-formed instructions are reported.
+    ///
+    /// Programs declaring variables are guarded-command programs:
+    /// their states are the valuations of the variables reachable through the rules.
+    /// Programs declaring a system are the parallel composition of their processes.
+    pub fn compile(self) -> Result<Ts<String, String>, Vec<CompileError>> {
+        let mut env = self.exec()?;
+        if env.guarded {
+            env.explore().map_err(|err| vec![err])?;
+        }
+        if let Some((processes, sync)) = env.system.as_ref() {
+            return Ok(env.compose(processes, sync));
+        }
+        let transitions = env.transitions.into_iter().flat_map(|(s, post)| {
+            post.into_iter()
+                .flat_map(move |(a, succ)| succ.into_iter().map(move |t| (s, a.clone(), t)))
+        });
+        let transitions = Transitions::new(&env.states, transitions);
+        Ok(Ts {
+            labels: transitions.to_label_bits(env.labels),
+            transitions,
+            states: env.states,
+            initial: env.initial,
+            spec: env.spec,
+            fairness: env.fairness,
+            names: env.names,
+        })
+    }
+
+    /// Compile the program into a symbolic system.
+    /// Guarded-command programs are encoded without enumerating their states,
+    /// the other programs are compiled into transition systems first.
+    #[cfg(feature = "symbolic")]
+    pub fn compile_symbolic(self) -> Result<Symbolic, Vec<CompileError>> {
+        if !self
+            .instructions
+            .iter()
+            .any(|instr| matches!(instr, Instr::DeclVar(_)))
+        {
+            return self.compile().map(|ts| Symbolic::from_ts(&ts));
+        }
+        let env = self.exec()?;
+        Symbolic::from_gcl(
+            env.vars,
+            &env.rules,
+            &env.definitions,
+            env.spec,
+            env.fairness,
+        )
+        .map_err(|err| vec![err])
+    }
+
+    /// Execute the instructions of the program, reporting all the ill-formed ones
+    fn exec(self) -> Result<ProgEnv, Vec<CompileError>> {
+        let ids = self.intern();
+        let (mut declared_props, mut declared_actions) = (HashSet::new(), HashSet::new());
+        for instr in self.instructions.iter() {
+            instr.declarations(&mut declared_props, &mut declared_actions);
+        }
         let mut env = ProgEnv {
+            declared_props,
+            declared_actions,
             props: HashSet::new(),
             actions: HashSet::new(),
+            names: ids.iter().map(|(name, id)| (*id, name.clone())).collect(),
+            guarded: self
+                .instructions
+                .iter()
+                .any(|instr| matches!(instr, Instr::DeclVar(_))),
+            vars: vec![],
+            domains: HashMap::new(),
+            rules: vec![],
+            definitions: vec![],
+            composed: self
+                .instructions
+                .iter()
+                .any(|instr| matches!(instr, Instr::System(_, _))),
+            processes: HashMap::new(),
+            system: None,
+            ids,
+            declared_states: false,
             states: HashSet::new(),
             spec: vec![],
+            fairness: vec![],
             initial: HashSet::new(),
             labels: HashMap::new(),
             transitions: HashMap::new(),
         };
-        for instr in self.instructions {
-            env.exec(instr)?;
+        let errors = self
+            .instructions
+            .into_iter()
+            .filter_map(|instr| env.exec(instr).err())
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(errors);
         }
-        Ok(Ts {
-            states: env.states,
-            initial: env.initial,
-            labels: env.labels,
-            transitions: env.transitions,
-            spec: env.spec,
-        })
+        Ok(env)
+    }
+
+    /// Number the named states of the program, in order of appearance.
+    /// Named states are numbered after the states given by their number
+    fn intern(&self) -> HashMap<String, u32> {
+        let states = self.instructions.iter().flat_map(Instr::states);
+        let mut next = states
+            .clone()
+            .filter_map(|s| match s {
+                State::Id(n) => Some(*n),
+                State::Name(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let mut ids = HashMap::new();
+        for s in states {
+            if let State::Name(name) = s {
+                ids.entry(name.clone()).or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                });
+            }
+        }
+        ids
     }
 }
 
 impl FromStr for Prog {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut buff = Buff::new(s.chars().collect());
-        buff.expect_list(Instr::parse)
-            .map(|instructions| Prog { instructions })
-            .and_then(|prog| {
-                buff.expect_end()?;
-                Some(prog)
-            })
-            .ok_or_else(|| "Prog: parse error".to_string())
+        let mut buff = Buff::from_text(s);
+        let mut instructions = vec![Instr::parse(&mut buff)?];
+        buff.trim()?;
+        while !buff.is_empty() {
+            instructions.push(Instr::parse(&mut buff)?);
+            buff.trim()?;
+        }
+        Ok(Prog { instructions })
     }
 }
 
@@ -226,7 +812,14 @@ impl FromStr for Prog {
 mod test_prog {
     use std::collections::{HashMap, HashSet};
 
-    use crate::{lang::Instr::*, lang::Prog, ts::Ts};
+    use crate::{
+        buff::{ParseError, Pos},
+        dense::Transitions,
+        lang::Instr::*,
+        lang::State::*,
+        lang::{CompileError, Prog},
+        ts::Ts,
+    };
 
     #[test]
     fn test_1() {
@@ -247,7 +840,7 @@ mod test_prog {
         assert_eq!(
             prog.parse::<Prog>(),
             Ok(Prog {
-                instructions: vec![SetInit(vec![1])]
+                instructions: vec![SetInit(vec![Id(1)])]
             })
         )
     }
@@ -259,7 +852,7 @@ mod test_prog {
         assert_eq!(
             prog.parse::<Prog>(),
             Ok(Prog {
-                instructions: vec![Label(1, vec!["P".to_string()])]
+                instructions: vec![Label(Id(1), vec!["P".to_string()])]
             })
         )
     }
@@ -270,7 +863,7 @@ mod test_prog {
         assert_eq!(
             prog.parse::<Prog>(),
             Ok(Prog {
-                instructions: vec![Trans(1, "act".to_string(), 2)]
+                instructions: vec![Trans(Id(1), "act".to_string(), Id(2))]
             })
         )
     }
@@ -282,7 +875,7 @@ mod test_prog {
         assert_eq!(
             prog.parse::<Prog>(),
             Ok(Prog {
-                instructions: vec![Loop(1, "act".to_string())]
+                instructions: vec![Loop(Id(1), "act".to_string())]
             })
         )
     }
@@ -293,7 +886,7 @@ mod test_prog {
         assert_eq!(
             prog.parse::<Prog>(),
             Ok(Prog {
-                instructions: vec![Loop(1, "act".to_string())]
+                instructions: vec![Loop(Id(1), "act".to_string())]
             })
         )
     }
@@ -351,8 +944,10 @@ mod test_prog {
                 states: HashSet::from([1, 2]),
                 initial: HashSet::from([]),
                 labels: HashMap::from([]),
-                transitions: HashMap::from([(1, HashMap::from([("act".to_string(), 2)]))]),
-                spec: vec![]
+                transitions: Transitions::new(&HashSet::from([1, 2]), [(1, "act".to_string(), 2)]),
+                spec: vec![],
+                fairness: vec![],
+                names: HashMap::new()
             })
         )
     }
@@ -366,9 +961,232 @@ mod test_prog {
                 states: HashSet::from([1, 2]),
                 initial: HashSet::from([1]),
                 labels: HashMap::from([]),
-                transitions: HashMap::from([(1, HashMap::from([("act".to_string(), 2)]))]),
-                spec: vec![]
+                transitions: Transitions::new(&HashSet::from([1, 2]), [(1, "act".to_string(), 2)]),
+                spec: vec![],
+                fairness: vec![],
+                names: HashMap::new()
             })
         )
     }
+
+    #[test]
+    fn test_15() {
+        let prog = "(actions act)(init 1)(trans 1 act 2)(trans 1 act 3)(loop 1 act)";
+        assert_eq!(
+            prog.parse::<Prog>().unwrap().compile(),
+            Ok(Ts {
+                states: HashSet::from([1, 2, 3]),
+                initial: HashSet::from([1]),
+                labels: HashMap::from([]),
+                transitions: Transitions::new(
+                    &HashSet::from([1, 2, 3]),
+                    [1, 2, 3].map(|t| (1, "act".to_string(), t))
+                ),
+                spec: vec![],
+                fairness: vec![],
+                names: HashMap::new()
+            })
+        )
+    }
+
+    #[test]
+    fn test_16() {
+        let prog = "(spec (lfp (x) (not x)))";
+        assert!(prog.parse::<Prog>().unwrap().compile().is_err());
+        let prog = "(spec (lfp (x) (not (gfp (y) (and (not x) y)))))";
+        assert!(prog.parse::<Prog>().unwrap().compile().is_ok());
+    }
+
+    #[test]
+    fn test_17() {
+        let prog = "(actions a)\n(init 1)\n(trans 1 a (b))\n";
+        assert_eq!(
+            prog.parse::<Prog>(),
+            Err(ParseError::new(Pos { line: 3, col: 12 }, "a state", "(b)"))
+        )
+    }
+
+    #[test]
+    fn test_18() {
+        let prog = "(props P)\n(spec\n  (and P (ag (foo P))))\n";
+        let err = prog.parse::<Prog>().unwrap_err();
+        assert_eq!(err.pos, Pos { line: 3, col: 15 });
+        assert_eq!(err.found, "foo");
+    }
+
+    #[test]
+    fn test_19() {
+        let prog = "(actions a)(props P)(trans 1 a 2)(trans 1 a 2)(loop 2 b)(label 1 Q)(actions b)";
+        assert_eq!(
+            prog.parse::<Prog>().unwrap().compile(),
+            Err(vec![
+                CompileError::DuplicateTransition(Trans(Id(1), "a".to_string(), Id(2))),
+                CompileError::UndeclaredAction("b".to_string(), Loop(Id(2), "b".to_string())),
+                CompileError::UndeclaredProposition(
+                    "Q".to_string(),
+                    Label(Id(1), vec!["Q".to_string()])
+                ),
+                CompileError::DuplicateActions(SetActions(vec!["b".to_string()])),
+            ])
+        )
+    }
+
+    #[test]
+    fn test_20() {
+        let prog = "; a model\r\n(actions req_sent msg.ack) #| no\r\n(init 1) |#\r\n(loop 1 msg.ack) ; done";
+        assert_eq!(
+            prog.parse::<Prog>(),
+            Ok(Prog {
+                instructions: vec![
+                    SetActions(vec!["req_sent".to_string(), "msg.ack".to_string()]),
+                    Loop(Id(1), "msg.ack".to_string())
+                ]
+            })
+        )
+    }
+
+    #[test]
+    fn test_21() {
+        let prog = "(actions go)(states idle busy)(init idle)(trans idle go busy)(trans busy go 1)";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        assert_eq!(ts.states, HashSet::from([1, 2, 3]));
+        assert_eq!(ts.initial, HashSet::from([2]));
+        assert_eq!(ts.state_name(2), "idle");
+        assert_eq!(ts.state_name(3), "busy");
+        assert_eq!(ts.state_name(1), "1");
+    }
+
+    #[test]
+    fn test_22() {
+        let prog = "
+(int x 0 2 0)
+(bool b false)
+(rule inc (< x 2) (set x (+ x 1)))
+(rule flip (= x 2) (set b (not b)) (set x 0))
+(prop Max (= x 2))
+(spec (ag (ef Max)))";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        assert_eq!(ts.states, HashSet::from([1, 2, 3, 4, 5, 6]));
+        assert_eq!(ts.initial, HashSet::from([1]));
+        assert_eq!(ts.state_name(1), "(x=0, b=false)");
+        assert_eq!(ts.state_name(4), "(x=0, b=true)");
+        assert_eq!(ts.label(&3), HashSet::from(["Max".to_string()]));
+        assert!(ts.check());
+    }
+
+    #[test]
+    fn test_23() {
+        let prog = "(int x 0 1 0)(rule inc true (set x (+ x 1)))";
+        assert!(matches!(
+            prog.parse::<Prog>().unwrap().compile().unwrap_err()[..],
+            [CompileError::EvalError(_, Rule(_))]
+        ));
+        let prog = "(int x 0 1 0)(rule inc x)(prop P y)(init 1)";
+        assert!(matches!(
+            prog.parse::<Prog>().unwrap().compile().unwrap_err()[..],
+            [
+                CompileError::IllTyped(_, Rule(_)),
+                CompileError::IllTyped(_, Prop(_, _)),
+                CompileError::MixedProgram(SetInit(_))
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_24() {
+        let prog = "
+(process client
+  (props Waiting)
+  (actions req ack)
+  (init idle)
+  (label wait Waiting)
+  (trans idle req wait)
+  (trans wait ack idle))
+(process server
+  (actions req work ack)
+  (init ready)
+  (trans ready req busy)
+  (trans busy work done)
+  (trans done ack ready))
+(system (par client server) (sync req ack))
+(spec (ag (af (not Waiting))))";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        assert_eq!(ts.states.len(), 3);
+        assert_eq!(ts.state_name(1), "(idle, ready)");
+        assert!(ts.check());
+    }
+
+    #[test]
+    fn test_25() {
+        let prog = "(process p (actions a) (trans 1 b 2))(system (par p q))(init 1)";
+        assert!(matches!(
+            prog.parse::<Prog>().unwrap().compile().unwrap_err()[..],
+            [
+                CompileError::InProcess(_, _),
+                CompileError::UndeclaredProcess(_, _),
+                CompileError::MixedProgram(SetInit(_))
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_26() {
+        let prog =
+            "(props P) (actions a) (init 1) (trans 1 a 2) (label 2 P)\n(spec \"<a>P && [a]P\")";
+        assert!(prog.parse::<Prog>().unwrap().compile().unwrap().check());
+        let prog =
+            "(actions a) (init 1) (trans 1 a 2) (spec \"<a>true\") (spec (not (any a false)))";
+        assert!(prog.parse::<Prog>().unwrap().compile().unwrap().check());
+        let prog = "(props P) (actions a b) (spec \"<a . b>P\") (spec \"<a.b>P\") (spec (ag Q))";
+        assert!(matches!(
+            &prog.parse::<Prog>().unwrap().compile().unwrap_err()[..],
+            [
+                CompileError::UndeclaredAction(a, SetSpec(_)),
+                CompileError::UndeclaredProposition(q, SetSpec(_))
+            ] if a == "a.b" && q == "Q"
+        ));
+        let prog = "(props P) (actions a)\n(spec \"mu X. P || <a>\")";
+        assert_eq!(
+            prog.parse::<Prog>().unwrap_err(),
+            ParseError::new(Pos { line: 2, col: 22 }, "a formula", "end of formula")
+        );
+    }
+
+    #[test]
+    fn test_27() {
+        // G is only reached eventually when grant is weakly fair
+        let prog = "(props G) (actions spin grant) (init 1) (loop 1 spin) (trans 1 grant 2)
+            (label 2 G) (loop 2 spin) (spec (af G)) (spec (f G))";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        assert_eq!(ts.violations().len(), 2);
+        let prog = format!("{} (fair weak grant)", prog);
+        let prog = prog.parse::<Prog>().unwrap();
+        assert_eq!(
+            prog.instructions.last().unwrap().to_string(),
+            "(fair weak grant)"
+        );
+        assert!(prog.compile().unwrap().check());
+        // Modalities only range over the states from which a fair path starts
+        let prog = "(props G) (actions spin grant) (init 1) (trans 1 grant 2) (trans 1 spin 3)
+            (loop 2 spin) (loop 3 spin) (label 2 G) (spec \"[true]G\")";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        assert!(!ts.check());
+        let prog = format!("{} (fair G)", prog);
+        assert!(prog.parse::<Prog>().unwrap().compile().unwrap().check());
+    }
+
+    #[test]
+    fn test_28() {
+        let prog = "(int y -2 3 1)
+(rule dec (> y -2) (set y (+ y -1)))
+(prop Min (= y -2))
+(spec (ef Min))";
+        let prog = prog.parse::<Prog>().unwrap();
+        assert_eq!(prog.instructions[0].to_string(), "(int y -2 3 1)");
+        let ts = prog.compile().unwrap();
+        assert_eq!(ts.states.len(), 4);
+        assert_eq!(ts.state_name(4), "(y=-2)");
+        assert_eq!(ts.label(&4), HashSet::from(["Min".to_string()]));
+        assert!(ts.check());
+    }
 }
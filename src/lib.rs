@@ -1,5 +1,26 @@
+pub mod act;
+pub mod aut;
+#[cfg(feature = "symbolic")]
+pub mod bdd;
+pub mod bisim;
+pub mod bits;
 pub mod buff;
+pub mod compose;
+pub mod ctl;
+pub mod dense;
+pub mod dot;
+pub mod equiv;
+pub mod explain;
+pub mod fair;
+pub mod game;
+pub mod gcl;
+pub mod graph;
 pub mod lang;
+pub mod ltl;
 pub mod mu;
+pub mod reg;
 pub mod sexpr;
+pub mod spec;
+#[cfg(feature = "symbolic")]
+pub mod symbolic;
 pub mod ts;
@@ -0,0 +1,588 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{
+    buff::{Buff, ParseError},
+    explain::Trace,
+    fair::Runs,
+    sexpr::Sexpr,
+    ts::Ts,
+};
+
+/// Formulas of the Linear Temporal Logic.
+///
+/// A state satisfies a formula if every maximal path starting from it does.
+/// A path ending in a deadlock is seen as staying forever in its last state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Ltl<P>
+where
+    P: Display,
+{
+    Lit(P),
+    Neg(Box<Ltl<P>>),
+    And(Box<Ltl<P>>, Box<Ltl<P>>),
+    Or(Box<Ltl<P>>, Box<Ltl<P>>),
+    Next(Box<Ltl<P>>),
+    Finally(Box<Ltl<P>>),
+    Globally(Box<Ltl<P>>),
+    Until(Box<Ltl<P>>, Box<Ltl<P>>),
+    Release(Box<Ltl<P>>, Box<Ltl<P>>),
+}
+
+/// Formulas in negation normal form, used to build automata
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Nnf<P> {
+    True,
+    False,
+    /// A proposition, or its negation
+    Lit(P, bool),
+    And(Box<Nnf<P>>, Box<Nnf<P>>),
+    Or(Box<Nnf<P>>, Box<Nnf<P>>),
+    Next(Box<Nnf<P>>),
+    Until(Box<Nnf<P>>, Box<Nnf<P>>),
+    Release(Box<Nnf<P>>, Box<Nnf<P>>),
+}
+
+impl<P> Ltl<P>
+where
+    P: Display + Clone,
+{
+    /// The propositions occurring in the formula
+    pub fn props(&self) -> Vec<P> {
+        match self {
+            Ltl::Lit(p) => vec![p.clone()],
+            Ltl::Neg(a) | Ltl::Next(a) | Ltl::Finally(a) | Ltl::Globally(a) => a.props(),
+            Ltl::And(a, b) | Ltl::Or(a, b) | Ltl::Until(a, b) | Ltl::Release(a, b) => {
+                let mut props = a.props();
+                props.extend(b.props());
+                props
+            }
+        }
+    }
+
+    /// Check if the formula uses the next-step operator `X`
+    pub fn has_next(&self) -> bool {
+        match self {
+            Ltl::Lit(_) => false,
+            Ltl::Next(_) => true,
+            Ltl::Neg(a) | Ltl::Finally(a) | Ltl::Globally(a) => a.has_next(),
+            Ltl::And(a, b) | Ltl::Or(a, b) | Ltl::Until(a, b) | Ltl::Release(a, b) => {
+                a.has_next() || b.has_next()
+            }
+        }
+    }
+
+    /// The formula (if `pos`) or its negation, in negation normal form
+    fn to_nnf(&self, pos: bool) -> Nnf<P> {
+        let nnf = |a: &Ltl<P>, pos| Box::new(a.to_nnf(pos));
+        match self {
+            Ltl::Lit(p) => Nnf::Lit(p.clone(), pos),
+            Ltl::Neg(a) => a.to_nnf(!pos),
+            Ltl::And(a, b) if pos => Nnf::And(nnf(a, pos), nnf(b, pos)),
+            Ltl::And(a, b) => Nnf::Or(nnf(a, pos), nnf(b, pos)),
+            Ltl::Or(a, b) if pos => Nnf::Or(nnf(a, pos), nnf(b, pos)),
+            Ltl::Or(a, b) => Nnf::And(nnf(a, pos), nnf(b, pos)),
+            Ltl::Next(a) => Nnf::Next(nnf(a, pos)),
+            Ltl::Finally(a) if pos => Nnf::Until(Box::new(Nnf::True), nnf(a, pos)),
+            Ltl::Finally(a) => Nnf::Release(Box::new(Nnf::False), nnf(a, pos)),
+            Ltl::Globally(a) if pos => Nnf::Release(Box::new(Nnf::False), nnf(a, pos)),
+            Ltl::Globally(a) => Nnf::Until(Box::new(Nnf::True), nnf(a, pos)),
+            Ltl::Until(a, b) if pos => Nnf::Until(nnf(a, pos), nnf(b, pos)),
+            Ltl::Until(a, b) => Nnf::Release(nnf(a, pos), nnf(b, pos)),
+            Ltl::Release(a, b) if pos => Nnf::Release(nnf(a, pos), nnf(b, pos)),
+            Ltl::Release(a, b) => Nnf::Until(nnf(a, pos), nnf(b, pos)),
+        }
+    }
+}
+
+impl<P> Nnf<P>
+where
+    P: Clone + Eq,
+{
+    /// The until subformulas, each one defining a set of accepting states
+    fn untils(&self, acc: &mut Vec<Nnf<P>>) {
+        match self {
+            Nnf::True | Nnf::False | Nnf::Lit(_, _) => (),
+            Nnf::Next(a) => a.untils(acc),
+            Nnf::And(a, b) | Nnf::Or(a, b) | Nnf::Release(a, b) => {
+                a.untils(acc);
+                b.untils(acc);
+            }
+            Nnf::Until(a, b) => {
+                a.untils(acc);
+                b.untils(acc);
+                if !acc.contains(self) {
+                    acc.push(self.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A node of the tableau: the formulas to prove now (`new` and `old`)
+/// and from the next step (`next`)
+#[derive(Clone)]
+struct Node<P> {
+    initial: bool,
+    incoming: HashSet<usize>,
+    new: Vec<Nnf<P>>,
+    old: Vec<Nnf<P>>,
+    next: Vec<Nnf<P>>,
+}
+
+impl<P> Node<P>
+where
+    P: Clone + Eq,
+{
+    fn add(formulas: &mut Vec<Nnf<P>>, f: Nnf<P>) {
+        if !formulas.contains(&f) {
+            formulas.push(f);
+        }
+    }
+
+    /// A copy of the node proving `now` now and `next` from the next step
+    fn split(&self, now: Vec<&Nnf<P>>, next: Option<&Nnf<P>>) -> Self {
+        let mut node = self.clone();
+        for f in now {
+            if !node.old.contains(f) {
+                Node::add(&mut node.new, f.clone());
+            }
+        }
+        if let Some(f) = next {
+            Node::add(&mut node.next, f.clone());
+        }
+        node
+    }
+}
+
+fn same_set<T: Eq>(lhs: &[T], rhs: &[T]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().all(|x| rhs.contains(x))
+}
+
+/// A generalized Büchi automaton whose states are labelled
+/// by the literals that must hold when entering them
+struct Buchi<P> {
+    literals: Vec<Vec<(P, bool)>>,
+    initial: Vec<usize>,
+    succ: Vec<Vec<usize>>,
+    /// Every accepting run visits each of these sets infinitely often
+    accepting: Vec<HashSet<usize>>,
+}
+
+impl<P> Buchi<P>
+where
+    P: Clone + Eq,
+{
+    /// Build the automaton of a formula with the tableau construction
+    /// of Gerth, Peled, Vardi and Wolper
+    fn new(formula: Nnf<P>) -> Self {
+        let mut nodes: Vec<Node<P>> = vec![];
+        let mut todo = vec![Node {
+            initial: true,
+            incoming: HashSet::new(),
+            new: vec![formula.clone()],
+            old: vec![],
+            next: vec![],
+        }];
+        while let Some(mut node) = todo.pop() {
+            let Some(f) = node.new.pop() else {
+                let same = nodes.iter_mut().find(|other| {
+                    same_set(&other.old, &node.old) && same_set(&other.next, &node.next)
+                });
+                if let Some(other) = same {
+                    other.initial |= node.initial;
+                    other.incoming.extend(node.incoming);
+                } else {
+                    todo.push(Node {
+                        initial: false,
+                        incoming: HashSet::from([nodes.len()]),
+                        new: node.next.clone(),
+                        old: vec![],
+                        next: vec![],
+                    });
+                    nodes.push(node);
+                }
+                continue;
+            };
+            if node.old.contains(&f) {
+                todo.push(node);
+                continue;
+            }
+            match &f {
+                Nnf::False => continue,
+                Nnf::Lit(p, b) if node.old.contains(&Nnf::Lit(p.clone(), !b)) => continue,
+                Nnf::True | Nnf::Lit(_, _) => (),
+                Nnf::And(a, b) => node = node.split(vec![a, b], None),
+                Nnf::Next(a) => node = node.split(vec![], Some(a)),
+                Nnf::Or(a, b) => {
+                    let mut other = node.split(vec![b], None);
+                    other.old.push(f.clone());
+                    todo.push(other);
+                    node = node.split(vec![a], None);
+                }
+                Nnf::Until(a, b) => {
+                    let mut other = node.split(vec![b], None);
+                    other.old.push(f.clone());
+                    todo.push(other);
+                    node = node.split(vec![a], Some(&f));
+                }
+                Nnf::Release(a, b) => {
+                    let mut other = node.split(vec![a, b], None);
+                    other.old.push(f.clone());
+                    todo.push(other);
+                    node = node.split(vec![b], Some(&f));
+                }
+            }
+            node.old.push(f);
+            todo.push(node);
+        }
+        let mut succ = vec![vec![]; nodes.len()];
+        for (j, node) in nodes.iter().enumerate() {
+            for i in node.incoming.iter() {
+                succ[*i].push(j);
+            }
+        }
+        let mut untils = vec![];
+        formula.untils(&mut untils);
+        let accepting = untils
+            .iter()
+            .map(|until| {
+                let Nnf::Until(_, b) = until else {
+                    unreachable!()
+                };
+                (0..nodes.len())
+                    .filter(|i| !nodes[*i].old.contains(until) || nodes[*i].old.contains(b))
+                    .collect()
+            })
+            .collect();
+        Buchi {
+            literals: nodes
+                .iter()
+                .map(|node| {
+                    node.old
+                        .iter()
+                        .filter_map(|f| match f {
+                            Nnf::Lit(p, b) => Some((p.clone(), *b)),
+                            _ => None,
+                        })
+                        .collect()
+                })
+                .collect(),
+            initial: (0..nodes.len()).filter(|i| nodes[*i].initial).collect(),
+            succ,
+            accepting,
+        }
+    }
+}
+
+/// The product of a transition system and of a Büchi automaton,
+/// restricted to the pairs reachable from some starting states.
+/// Deadlocks of the system loop through steps without action.
+struct Product<A> {
+    pairs: Vec<(u32, usize)>,
+    edges: Vec<Vec<(Option<A>, u32)>>,
+    initial: HashMap<u32, Vec<u32>>,
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    fn enters(&self, x: u32, literals: &[(P, bool)]) -> bool {
+        literals.iter().all(|(p, b)| self.has_label(&x, p) == *b)
+    }
+
+    fn product(&self, buchi: &Buchi<P>, starts: &[u32]) -> Product<A> {
+        let mut product = Product {
+            pairs: vec![],
+            edges: vec![],
+            initial: HashMap::new(),
+        };
+        let mut ids = HashMap::new();
+        let mut id = |pair: (u32, usize), product: &mut Product<A>, todo: &mut Vec<u32>| {
+            *ids.entry(pair).or_insert_with(|| {
+                product.pairs.push(pair);
+                product.edges.push(vec![]);
+                todo.push(product.pairs.len() as u32 - 1);
+                product.pairs.len() as u32 - 1
+            })
+        };
+        let mut todo = vec![];
+        for x in starts {
+            for q in buchi.initial.iter() {
+                if self.enters(*x, &buchi.literals[*q]) {
+                    let i = id((*x, *q), &mut product, &mut todo);
+                    product.initial.entry(*x).or_default().push(i);
+                }
+            }
+        }
+        while let Some(i) = todo.pop() {
+            let (x, q) = product.pairs[i as usize];
+            let mut steps = self
+                .transitions
+                .post(&x)
+                .map(|(a, y)| (Some(a.clone()), *y))
+                .collect::<Vec<_>>();
+            if steps.is_empty() {
+                steps.push((None, x));
+            }
+            for (a, y) in steps {
+                for r in buchi.succ[q].iter() {
+                    if self.enters(y, &buchi.literals[*r]) {
+                        let j = id((y, *r), &mut product, &mut todo);
+                        product.edges[i as usize].push((a.clone(), j));
+                    }
+                }
+            }
+        }
+        product
+    }
+}
+
+impl<A> Runs<A> for Product<A>
+where
+    A: Clone,
+{
+    fn next_steps(&self, i: u32) -> Vec<(Option<A>, u32)> {
+        self.edges[i as usize].clone()
+    }
+
+    fn state_of(&self, i: u32) -> u32 {
+        self.pairs[i as usize].0
+    }
+}
+
+impl<A> Product<A> {
+    /// The sets of pairs to visit infinitely often in an accepting run
+    fn visit<P>(&self, buchi: &Buchi<P>) -> Vec<HashSet<u32>> {
+        buchi
+            .accepting
+            .iter()
+            .map(|set| {
+                (0..self.pairs.len() as u32)
+                    .filter(|i| set.contains(&self.pairs[*i as usize].1))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Check if a pair of `target` is reachable from `from`
+    fn path_exists(&self, from: u32, target: &HashSet<u32>) -> bool {
+        let mut todo = vec![from];
+        let mut seen = HashSet::from([from]);
+        while let Some(i) = todo.pop() {
+            if target.contains(&i) {
+                return true;
+            }
+            for (_, j) in self.edges[i as usize].iter() {
+                if seen.insert(*j) {
+                    todo.push(*j);
+                }
+            }
+        }
+        false
+    }
+}
+
+impl<A, P> Ts<A, P>
+where
+    A: Display + Eq + Clone + Hash,
+    P: Eq + Display + Clone + Hash,
+{
+    /// Compute the set of states satisfying an LTL formula: the states from
+    /// which the product with the automaton of the negated formula has no
+    /// accepting (and fair) run
+    pub fn sat_ltl(&self, ltl: &Ltl<P>) -> HashSet<u32> {
+        let buchi = Buchi::new(ltl.to_nnf(false));
+        let states = self.states.iter().cloned().collect::<Vec<_>>();
+        let product = self.product(&buchi, &states);
+        let mut pre = vec![vec![]; product.pairs.len()];
+        for (i, edges) in product.edges.iter().enumerate() {
+            for (_, j) in edges {
+                pre[*j as usize].push(i as u32);
+            }
+        }
+        let all = (0..product.pairs.len() as u32).collect();
+        let mut bad = self
+            .fair_components(&product, &all, &product.visit(&buchi))
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+        let mut todo = bad.iter().cloned().collect::<Vec<_>>();
+        while let Some(j) = todo.pop() {
+            for i in pre[j as usize].iter() {
+                if bad.insert(*i) {
+                    todo.push(*i);
+                }
+            }
+        }
+        self.states
+            .iter()
+            .filter(|x| {
+                product
+                    .initial
+                    .get(x)
+                    .is_none_or(|pairs| pairs.iter().all(|i| !bad.contains(i)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// A (fair) path from `state` violating an LTL formula, if any: a lasso,
+    /// or a finite path ending in a deadlock
+    pub fn ltl_counterexample(&self, ltl: &Ltl<P>, state: u32) -> Option<Trace<A>> {
+        let buchi = Buchi::new(ltl.to_nnf(false));
+        let product = self.product(&buchi, &[state]);
+        let all = (0..product.pairs.len() as u32).collect::<HashSet<_>>();
+        let visit = product.visit(&buchi);
+        let components = self.fair_components(&product, &all, &visit);
+        let accepting = components.iter().flatten().cloned().collect::<HashSet<_>>();
+        let start = *product
+            .initial
+            .get(&state)?
+            .iter()
+            .find(|i| product.path_exists(**i, &accepting))?;
+        let mut steps = product.path_within(start, |j| accepting.contains(&j), &all, false);
+        let entry = steps.last().map_or(start, |(_, j)| *j);
+        let component = components.iter().find(|c| c.contains(&entry)).unwrap();
+        let lasso = steps.len();
+        steps.extend(self.fair_cycle(&product, entry, component, &visit));
+        // Project the run on the system, stopping at the first deadlock
+        let mut trace = Trace::new(state);
+        for (a, j) in steps.iter() {
+            match a {
+                Some(a) => trace.steps.push((a.clone(), product.pairs[*j as usize].0)),
+                None => return Some(trace),
+            }
+        }
+        trace.lasso = Some(lasso);
+        Some(trace)
+    }
+}
+
+impl Ltl<String> {
+    fn parse_binop(buff: &mut Buff<Sexpr>, is_or: bool) -> Result<Self, ParseError> {
+        let args = buff.convert_list(Self::from_sexpr)?;
+        let res = if is_or {
+            args.into_iter()
+                .reduce(|lhs, rhs| Ltl::Or(Box::new(lhs), Box::new(rhs)))
+        } else {
+            args.into_iter()
+                .reduce(|lhs, rhs| Ltl::And(Box::new(lhs), Box::new(rhs)))
+        };
+        Ok(res.unwrap())
+    }
+
+    fn parse_unary(buff: &mut Buff<Sexpr>) -> Result<Box<Self>, ParseError> {
+        let ltl = buff.expect_convert(Self::from_sexpr)?;
+        buff.expect_end()?;
+        Ok(Box::new(ltl))
+    }
+
+    fn parse_binary(buff: &mut Buff<Sexpr>) -> Result<(Box<Self>, Box<Self>), ParseError> {
+        let lhs = Box::new(buff.expect_convert(Self::from_sexpr)?);
+        let rhs = Box::new(buff.expect_convert(Self::from_sexpr)?);
+        buff.expect_end()?;
+        Ok((lhs, rhs))
+    }
+
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        match sexpr {
+            Sexpr::Sym(s, _) => Ok(Ltl::Lit(s)),
+            Sexpr::Num(_, _) | Sexpr::Str(_, _) => Err(sexpr.error("an LTL formula")),
+            Sexpr::List(_, _) => {
+                let mut buff = sexpr.into_buff("an LTL formula")?;
+                let pos = buff.position();
+                let op = buff.expect_symb()?;
+                match op.as_str() {
+                    "x" => Ok(Ltl::Next(Self::parse_unary(&mut buff)?)),
+                    "f" => Ok(Ltl::Finally(Self::parse_unary(&mut buff)?)),
+                    "g" => Ok(Ltl::Globally(Self::parse_unary(&mut buff)?)),
+                    "u" => Self::parse_binary(&mut buff).map(|(a, b)| Ltl::Until(a, b)),
+                    "r" => Self::parse_binary(&mut buff).map(|(a, b)| Ltl::Release(a, b)),
+                    "not" => Ok(Ltl::Neg(Self::parse_unary(&mut buff)?)),
+                    "or" | "and" => Self::parse_binop(&mut buff, op == "or"),
+                    _ => Err(ParseError::new(pos, "an LTL operator", op)),
+                }
+            }
+        }
+    }
+}
+
+impl<P> Display for Ltl<P>
+where
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ltl::Lit(p) => write!(f, "{}", p),
+            Ltl::Neg(a) => write!(f, "¬{}", a),
+            Ltl::And(a, b) => write!(f, "({} ∧ {})", a, b),
+            Ltl::Or(a, b) => write!(f, "({} ∨ {})", a, b),
+            Ltl::Next(a) => write!(f, "X {}", a),
+            Ltl::Finally(a) => write!(f, "F {}", a),
+            Ltl::Globally(a) => write!(f, "G {}", a),
+            Ltl::Until(a, b) => write!(f, "({} U {})", a, b),
+            Ltl::Release(a, b) => write!(f, "({} R {})", a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{ltl::Ltl, sexpr::Sexpr, spec::Spec, ts::Ts};
+
+    fn parse(s: &str) -> Ltl<String> {
+        Ltl::from_sexpr(s.parse::<Sexpr>().unwrap()).unwrap()
+    }
+
+    /// A client which may retry its request forever: 1 -req-> 2 -retry-> 2 -grant-> 3 -done-> 1
+    fn system() -> Ts<String, String> {
+        let s = |s: &str| s.to_string();
+        Ts::new::<Spec<String, String>>(
+            vec![1, 2, 3, 4],
+            vec![1],
+            vec![(2, vec![s("req")]), (3, vec![s("grant")])],
+            vec![
+                (1, vec![(s("req"), 2), (s("stop"), 4)]),
+                (2, vec![(s("retry"), 2), (s("grant"), 3)]),
+                (3, vec![(s("done"), 1)]),
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_1() {
+        let ltl = parse("(g (or (not req) (f grant)))");
+        assert_eq!(ltl.to_string(), "G (¬req ∨ F grant)");
+        let ts = system();
+        assert_eq!(ts.sat_ltl(&ltl), HashSet::from([4]));
+        let trace = ts.ltl_counterexample(&ltl, 1).unwrap();
+        // The request is retried forever
+        assert_eq!(trace.steps[0], ("req".to_string(), 2));
+        let lasso = trace.lasso.unwrap();
+        assert!(trace.steps[lasso..]
+            .iter()
+            .all(|step| *step == ("retry".to_string(), 2)));
+        assert_eq!(ts.ltl_counterexample(&ltl, 4), None);
+    }
+
+    #[test]
+    fn test_2() {
+        // The deadlock 4 stays forever in a state which is not labelled by req
+        let ts = system();
+        let ltl = parse("(f (or req grant))");
+        assert_eq!(ts.sat_ltl(&ltl), HashSet::from([2, 3]));
+        let trace = ts.ltl_counterexample(&ltl, 1).unwrap();
+        assert_eq!(trace.steps, vec![("stop".to_string(), 4)]);
+        assert_eq!(trace.lasso, None);
+        assert_eq!(ts.sat_ltl(&parse("(x (not req))")), HashSet::from([3, 4]));
+    }
+}
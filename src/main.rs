@@ -1,18 +1,223 @@
 use std::{env::args, fs};
 
-use mcmu::lang::Prog;
+use mcmu::{
+    dot::DotOptions,
+    equiv::Relation,
+    explain::Evidence,
+    lang::{CompileError, Prog},
+    ts::{Backend, Ts},
+};
 
-fn run(filename: &str) -> Result<bool, String> {
+#[cfg(feature = "symbolic")]
+use mcmu::symbolic::Symbolic;
+
+/// Outputs requested besides the verification
+struct Outputs<'a> {
+    /// Aldebaran file
+    aut: Option<&'a str>,
+    /// DOT file, and whether to cluster the states by label
+    dot: Option<(&'a str, bool)>,
+}
+
+/// Reduction applied to the system before checking it
+enum Reduction {
+    None,
+    Strong,
+    Branching(String),
+}
+
+/// Load a system from a model, or from an Aldebaran file
+fn load(filename: &str) -> Result<Ts<String, String>, String> {
     let input = fs::read_to_string(filename).map_err(|err| format!("{}", err))?;
-    let prog = input.parse::<Prog>()?;
-    let ts = prog.compile()?;
-    Ok(ts.check())
+    if filename.ends_with(".aut") {
+        return Ts::from_aut(&input).map_err(|err| err.to_string());
+    }
+    let prog = input.parse::<Prog>().map_err(|err| err.to_string())?;
+    prog.compile().map_err(report)
+}
+
+fn report(errors: Vec<CompileError>) -> String {
+    errors
+        .iter()
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Check a model with the symbolic backend, without enumerating
+/// the states of guarded-command programs
+#[cfg(feature = "symbolic")]
+fn run_symbolic(filename: &str) -> Result<bool, String> {
+    let input = fs::read_to_string(filename).map_err(|err| format!("{}", err))?;
+    let mut sys = if filename.ends_with(".aut") {
+        Symbolic::from_ts(&Ts::from_aut(&input).map_err(|err| err.to_string())?)
+    } else {
+        let prog = input.parse::<Prog>().map_err(|err| err.to_string())?;
+        prog.compile_symbolic().map_err(report)?
+    };
+    let violations = sys.violations()?;
+    for violation in violations.iter() {
+        println!(
+            "Specification {} is violated in state {}",
+            violation.spec, violation.state
+        );
+    }
+    Ok(violations.is_empty())
+}
+
+#[cfg(not(feature = "symbolic"))]
+fn run_symbolic(_: &str) -> Result<bool, String> {
+    Err("the symbolic backend is not enabled (build with --features symbolic)".to_string())
+}
+
+fn run(
+    filename: &str,
+    backend: Backend,
+    reduction: &Reduction,
+    outputs: &Outputs,
+) -> Result<bool, String> {
+    let ts = load(filename)?;
+    let unpreserved = match reduction {
+        Reduction::None => None,
+        Reduction::Strong => ts.unpreserved_spec(false).map(|spec| (spec, "strong")),
+        Reduction::Branching(_) => ts.unpreserved_spec(true).map(|spec| (spec, "branching")),
+    };
+    if let Some((spec, relation)) = unpreserved {
+        return Err(format!(
+            "the specification {} is not preserved by {} bisimulation",
+            spec, relation
+        ));
+    }
+    let ts = match reduction {
+        Reduction::None => ts,
+        Reduction::Strong => ts.minimize(),
+        Reduction::Branching(tau) => ts.minimize_branching(tau),
+    };
+    if let Some(output) = outputs.aut {
+        fs::write(output, ts.to_aut()?).map_err(|err| format!("{}", err))?;
+    }
+    let violations = ts.violations_with(backend);
+    for (spec, explanation) in violations.iter() {
+        println!(
+            "Specification {} is violated in state {}",
+            spec,
+            ts.state_name(explanation.state)
+        );
+        println!("{}", ts.named(explanation));
+    }
+    if let Some((output, clusters)) = outputs.dot {
+        // Highlight the first violated specification and its counterexample
+        let violation = violations.first();
+        let options = DotOptions {
+            clusters,
+            spec: violation.map(|(spec, _)| (*spec).clone()),
+            trace: violation.and_then(|(_, explanation)| match &explanation.evidence {
+                Evidence::Trace(trace) => Some(trace.clone()),
+                Evidence::Strategy(_) => None,
+            }),
+            ..Default::default()
+        };
+        fs::write(output, ts.to_dot(&options)).map_err(|err| format!("{}", err))?;
+    }
+    Ok(violations.is_empty())
+}
+
+fn compare(left: &str, right: &str, relation: &Relation<String>) -> Result<bool, String> {
+    let ts = load(left)?;
+    let other = load(right)?;
+    match ts.compare(&other, relation) {
+        Ok(()) => Ok(true),
+        Err(distinction) => {
+            let (holds, fails) = match distinction.first {
+                true => (left, right),
+                false => (right, left),
+            };
+            println!(
+                "Formula {} holds in {} but not in {}",
+                distinction.formula, holds, fails
+            );
+            Ok(false)
+        }
+    }
 }
 
 fn main() {
-    let file = args().collect::<Vec<String>>()[1].clone();
-    match run(file.as_str()) {
-        Ok(b) => println!("Result of the verification: {}", b),
-        Err(err) => eprintln!("Verification failed: {}", err),
+    let args = args().skip(1).collect::<Vec<String>>();
+    let backend = if args.iter().any(|arg| arg == "--game") {
+        Backend::Game
+    } else {
+        Backend::Fixpoint
+    };
+    let reduction = if let Some(tau) = args.iter().find_map(|arg| arg.strip_prefix("--branching="))
+    {
+        Reduction::Branching(tau.to_string())
+    } else if args.iter().any(|arg| arg == "--minimize") {
+        Reduction::Strong
+    } else {
+        Reduction::None
+    };
+    let outputs = Outputs {
+        aut: args.iter().find_map(|arg| arg.strip_prefix("--export=")),
+        dot: args
+            .iter()
+            .find_map(|arg| arg.strip_prefix("--dot="))
+            .map(|file| (file, args.iter().any(|arg| arg == "--cluster"))),
+    };
+    let tau = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--tau="))
+        .unwrap_or("tau");
+    let relation = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--compare="))
+        .map(|relation| match relation {
+            "strong" => Ok(Relation::Strong),
+            "weak" => Ok(Relation::Weak(tau.to_string())),
+            "simulation" => Ok(Relation::Simulation),
+            "traces" => Ok(Relation::Traces),
+            _ => Err(relation),
+        });
+    let files = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>();
+    let symbolic = args.iter().any(|arg| arg == "--symbolic");
+    // Flags of the explicit backend
+    let explicit = args.iter().find(|arg| {
+        [
+            "--game",
+            "--minimize",
+            "--branching=",
+            "--export=",
+            "--dot=",
+        ]
+        .iter()
+        .any(|flag| arg.starts_with(flag))
+    });
+    match (relation, files.as_slice()) {
+        (None, [_]) if symbolic && explicit.is_some() => {
+            eprintln!("{} cannot be used with --symbolic", explicit.unwrap());
+            usage();
+        }
+        (None, [file]) if symbolic => match run_symbolic(file.as_str()) {
+            Ok(b) => println!("Result of the verification: {}", b),
+            Err(err) => eprintln!("Verification failed: {}", err),
+        },
+        (None, [file]) => match run(file.as_str(), backend, &reduction, &outputs) {
+            Ok(b) => println!("Result of the verification: {}", b),
+            Err(err) => eprintln!("Verification failed: {}", err),
+        },
+        (Some(Ok(relation)), [left, right]) => match compare(left, right, &relation) {
+            Ok(b) => println!("Result of the comparison: {}", b),
+            Err(err) => eprintln!("Comparison failed: {}", err),
+        },
+        (Some(Err(relation)), _) => eprintln!("Unknown relation: {}", relation),
+        _ => usage(),
     }
 }
+
+fn usage() {
+    eprintln!("Usage: mcmu [--game] [--minimize | --branching=<act>] [--export=<file.aut>] [--dot=<file.dot> [--cluster]] <file>");
+    eprintln!("       mcmu --symbolic <file>");
+    eprintln!("       mcmu --compare=<relation> [--tau=<act>] <file> <file>");
+}
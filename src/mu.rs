@@ -1,6 +1,11 @@
 use std::{fmt::Display, str::FromStr};
 
-use crate::{buff::Buff, sexpr::Sexpr};
+use crate::{
+    act::Act,
+    buff::{Buff, ParseError, Pos},
+    reg::Reg,
+    sexpr::Sexpr,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Mu<A, P>
@@ -14,53 +19,211 @@ where
     And(Box<Mu<A, P>>, Box<Mu<A, P>>),
     Or(Box<Mu<A, P>>, Box<Mu<A, P>>),
     Gfp(String, Box<Mu<A, P>>),
-    All(A, Box<Mu<A, P>>),
+    All(Act<A>, Box<Mu<A, P>>),
     Lfp(String, Box<Mu<A, P>>),
-    Ex(A, Box<Mu<A, P>>),
+    Ex(Act<A>, Box<Mu<A, P>>),
     Var(String),
+    /// Converse box: the formula holds in every predecessor through the actions
+    AllPred(Act<A>, Box<Mu<A, P>>),
+    /// Converse diamond: the formula holds in some predecessor through the actions
+    ExPred(Act<A>, Box<Mu<A, P>>),
 }
 
-impl Mu<char, u32> {
-    fn parse_var(buff: &mut Buff<char>) -> Option<char> {
-        let c = buff.next()?;
-        match c {
-            'a'..='z' | 'A'..='Z' => Some(c),
-            _ => None,
+impl<A, P> Mu<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+    P: Clone,
+{
+    /// The constant `true` or `false`, as the fixpoint `νX. X` or `μX. X`
+    pub fn constant(value: bool) -> Self {
+        let x = value.to_string();
+        let body = Box::new(Mu::Var(x.clone()));
+        if value {
+            Mu::Gfp(x, body)
+        } else {
+            Mu::Lfp(x, body)
+        }
+    }
+
+    fn collect_signature(&self, props: &mut Vec<P>, actions: &mut Vec<A>)
+    where
+        A: Eq,
+    {
+        match self {
+            Mu::Lit(p) => props.push(p.clone()),
+            Mu::Var(_) => (),
+            Mu::Neg(a) | Mu::Lfp(_, a) | Mu::Gfp(_, a) => a.collect_signature(props, actions),
+            Mu::And(a, b) | Mu::Or(a, b) => {
+                a.collect_signature(props, actions);
+                b.collect_signature(props, actions);
+            }
+            Mu::All(act, a) | Mu::Ex(act, a) | Mu::AllPred(act, a) | Mu::ExPred(act, a) => {
+                actions.extend(act.actions().into_iter().cloned());
+                a.collect_signature(props, actions);
+            }
+        }
+    }
+
+    /// The propositions and the actions occurring in the formula
+    pub fn signature(&self) -> (Vec<P>, Vec<A>)
+    where
+        A: Eq,
+    {
+        let (mut props, mut actions) = (vec![], vec![]);
+        self.collect_signature(&mut props, &mut actions);
+        (props, actions)
+    }
+
+    /// Check if the formula has a modality over successors,
+    /// or over predecessors if `converse`
+    pub fn has_modality(&self, converse: bool) -> bool {
+        match self {
+            Mu::Lit(_) | Mu::Var(_) => false,
+            Mu::Neg(a) | Mu::Lfp(_, a) | Mu::Gfp(_, a) => a.has_modality(converse),
+            Mu::And(a, b) | Mu::Or(a, b) => a.has_modality(converse) || b.has_modality(converse),
+            Mu::All(_, a) | Mu::Ex(_, a) => !converse || a.has_modality(converse),
+            Mu::AllPred(_, a) | Mu::ExPred(_, a) => converse || a.has_modality(converse),
         }
     }
 
-    fn parse_act(buff: &mut Buff<char>) -> Option<char> {
-        buff.next()
+    /// Push the negations of the formula (negated if `neg`) down to the literals.
+    /// `bound` records the polarity of the binders in scope.
+    fn pnf(&self, neg: bool, bound: &mut Vec<(String, bool)>) -> Self {
+        match self {
+            Mu::Lit(_) if neg => Mu::Neg(Box::new(self.clone())),
+            Mu::Lit(_) => self.clone(),
+            Mu::Var(x) => {
+                let polarity = bound.iter().rev().find(|(y, _)| y == x).map(|(_, p)| *p);
+                if polarity.map_or(neg, |p| p != neg) {
+                    Mu::Neg(Box::new(self.clone()))
+                } else {
+                    self.clone()
+                }
+            }
+            Mu::Neg(a) => a.pnf(!neg, bound),
+            Mu::And(a, b) | Mu::Or(a, b) => {
+                let a = Box::new(a.pnf(neg, bound));
+                let b = Box::new(b.pnf(neg, bound));
+                if matches!(self, Mu::And(_, _)) != neg {
+                    Mu::And(a, b)
+                } else {
+                    Mu::Or(a, b)
+                }
+            }
+            Mu::All(act, a) | Mu::Ex(act, a) => {
+                let a = Box::new(a.pnf(neg, bound));
+                if matches!(self, Mu::All(_, _)) != neg {
+                    Mu::All(act.clone(), a)
+                } else {
+                    Mu::Ex(act.clone(), a)
+                }
+            }
+            Mu::AllPred(act, a) | Mu::ExPred(act, a) => {
+                let a = Box::new(a.pnf(neg, bound));
+                if matches!(self, Mu::AllPred(_, _)) != neg {
+                    Mu::AllPred(act.clone(), a)
+                } else {
+                    Mu::ExPred(act.clone(), a)
+                }
+            }
+            Mu::Lfp(x, a) | Mu::Gfp(x, a) => {
+                bound.push((x.clone(), neg));
+                let a = Box::new(a.pnf(neg, bound));
+                bound.pop();
+                if matches!(self, Mu::Lfp(_, _)) != neg {
+                    Mu::Lfp(x.clone(), a)
+                } else {
+                    Mu::Gfp(x.clone(), a)
+                }
+            }
+        }
     }
 
-    fn parse_atom(buff: &mut Buff<char>) -> Option<Self> {
-        let c = buff.top()?;
+    /// Positive normal form of the formula: negations are pushed down to
+    /// the literals by dualising connectives, modalities and fixpoints.
+    ///
+    /// Occurrences of variables under an odd number of negations
+    /// (see [`Mu::check_monotone`]) are left negated.
+    pub fn to_pnf(&self) -> Self {
+        self.pnf(false, &mut vec![])
+    }
+
+    fn monotone(&self, neg: bool, bound: &mut Vec<(String, bool)>) -> Result<(), String> {
+        match self {
+            Mu::Lit(_) => Ok(()),
+            Mu::Var(x) => match bound.iter().rev().find(|(y, _)| y == x) {
+                Some((_, polarity)) if *polarity != neg => Err(format!(
+                    "Non-monotone formula: variable {} occurs under an odd number of negations",
+                    x
+                )),
+                _ => Ok(()),
+            },
+            Mu::Neg(a) => a.monotone(!neg, bound),
+            Mu::And(a, b) | Mu::Or(a, b) => {
+                a.monotone(neg, bound)?;
+                b.monotone(neg, bound)
+            }
+            Mu::All(_, a) | Mu::Ex(_, a) | Mu::AllPred(_, a) | Mu::ExPred(_, a) => {
+                a.monotone(neg, bound)
+            }
+            Mu::Lfp(x, a) | Mu::Gfp(x, a) => {
+                bound.push((x.clone(), neg));
+                let res = a.monotone(neg, bound);
+                bound.pop();
+                res
+            }
+        }
+    }
+
+    /// Check that every bound variable occurs under an even number of
+    /// negations (relatively to its binder), which ensures that the body
+    /// of every fixpoint is monotone.
+    pub fn check_monotone(&self) -> Result<(), String> {
+        self.monotone(false, &mut vec![])
+    }
+}
+
+impl Mu<char, u32> {
+    fn parse_var(buff: &mut Buff<char>) -> Result<char, ParseError> {
+        buff.expect_alpha()
+    }
+
+    fn parse_act(buff: &mut Buff<char>) -> Result<char, ParseError> {
+        buff.expect_next("an action")
+    }
+
+    fn parse_atom(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        let Some(c) = buff.top() else {
+            return Err(buff.error("a formula"));
+        };
         match c {
             '0'..='9' => {
                 let n = buff.expect_u32()?;
-                Some(Mu::Lit(n))
+                Ok(Mu::Lit(n))
             }
             'a'..='z' | 'A'..='Z' => {
                 buff.pop();
-                Some(Mu::Var(c.to_string()))
+                Ok(Mu::Var(c.to_string()))
             }
             '(' => {
                 buff.pop();
                 let mu = Self::parse_disj(buff)?;
                 buff.expect(')')?;
-                Some(mu)
+                Ok(mu)
             }
             '⟨' => {
                 buff.pop();
                 let act = Self::parse_act(buff)?;
                 buff.expect('⟩')?;
-                Self::parse_atom(buff).map(|lhs| Mu::Ex(act, Box::new(lhs)))
+                Self::parse_atom(buff).map(|lhs| Mu::Ex(Act::One(act), Box::new(lhs)))
             }
             '[' => {
                 buff.pop();
                 let act = Self::parse_act(buff)?;
                 buff.expect(']')?;
-                Self::parse_atom(buff).map(|lhs| Mu::All(act, Box::new(lhs)))
+                Self::parse_atom(buff).map(|lhs| Mu::All(Act::One(act), Box::new(lhs)))
             }
             '¬' => {
                 buff.pop();
@@ -71,74 +234,289 @@ impl Mu<char, u32> {
                 let x = Self::parse_var(buff)?;
                 buff.expect('.')?;
                 let lhs = Self::parse_disj(buff)?;
-                Some(Mu::Lfp(x.to_string(), Box::new(lhs)))
+                Ok(Mu::Lfp(x.to_string(), Box::new(lhs)))
             }
             'ν' => {
                 buff.pop();
                 let x = Self::parse_var(buff)?;
                 buff.expect('.')?;
                 let lhs = Self::parse_disj(buff)?;
-                Some(Mu::Gfp(x.to_string(), Box::new(lhs)))
+                Ok(Mu::Gfp(x.to_string(), Box::new(lhs)))
             }
-            _ => None,
+            _ => Err(buff.error("a formula")),
         }
     }
 
-    fn parse_disj(buff: &mut Buff<char>) -> Option<Self> {
+    fn parse_disj(buff: &mut Buff<char>) -> Result<Self, ParseError> {
         let mut lhs = Self::parse_conj(buff)?;
         while let Some('∨') = buff.top() {
             buff.pop();
             let rhs = Self::parse_conj(buff)?;
             lhs = Mu::Or(Box::new(lhs), Box::new(rhs));
         }
-        Some(lhs)
+        Ok(lhs)
     }
 
-    fn parse_conj(buff: &mut Buff<char>) -> Option<Self> {
+    fn parse_conj(buff: &mut Buff<char>) -> Result<Self, ParseError> {
         let mut lhs = Self::parse_atom(buff)?;
         while let Some('∧') = buff.top() {
             buff.pop();
             let rhs = Self::parse_atom(buff)?;
             lhs = Mu::And(Box::new(lhs), Box::new(rhs));
         }
-        Some(lhs)
+        Ok(lhs)
     }
 
-    fn parse(buff: &mut Buff<char>) -> Option<Self> {
-        let res = Self::parse_disj(buff);
-        if buff.next().is_some() {
-            None
-        } else {
-            res
-        }
+    fn parse(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        let res = Self::parse_disj(buff)?;
+        buff.expect_end()?;
+        Ok(res)
     }
 }
 
 impl FromStr for Mu<char, u32> {
-    type Err = String;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut buff = Buff::new(s.chars().filter(|c| *c != ' ').collect());
-        Self::parse(&mut buff).ok_or_else(|| "Error while parsing Mu formula".to_string())
+        let (chars, end) = Pos::locate(s);
+        let chars = chars.into_iter().filter(|(c, _)| *c != ' ').collect();
+        Self::parse(&mut Buff::located(chars, end, "end of input"))
+    }
+}
+
+/// Consume one of the alternative spellings of a token
+fn expect_token(buff: &mut Buff<char>, tokens: &[&str]) -> Result<(), ParseError> {
+    if tokens.iter().any(|token| buff.eat(token)) {
+        Ok(())
+    } else {
+        Err(buff.error(tokens.join(" or ")))
     }
 }
 
 impl Mu<String, String> {
-    fn parse_binop(buff: &mut Buff<Sexpr>, is_or: bool) -> Option<Self> {
-        let args = buff.convert_list(Self::from_sexpr)?;
+    /// Identifier: a letter or `_` followed by letters, digits and the
+    /// characters `_`, `'`, and also `-` and `.` unless it is a variable
+    fn parse_ident(buff: &mut Buff<char>, is_var: bool) -> Result<String, ParseError> {
+        buff.trim()?;
+        let expected = if is_var {
+            "a variable"
+        } else {
+            "an identifier"
+        };
+        let first = buff.expect_cond(|c| c.is_alphabetic() || *c == '_', expected)?;
+        let mut ident = first.to_string();
+        while let Some(c) = buff.top() {
+            let accepted = c.is_alphanumeric()
+                || ['_', '\''].contains(&c)
+                || !is_var && ['-', '.'].contains(&c);
+            if !accepted {
+                break;
+            }
+            buff.pop();
+            ident.push(c);
+        }
+        Ok(ident)
+    }
+
+    fn parse_text_fixpoint(buff: &mut Buff<char>, is_lfp: bool) -> Result<Self, ParseError> {
+        let x = Self::parse_ident(buff, true)?;
+        expect_token(buff, &["."])?;
+        let mu = Self::parse_text_disj(buff)?.lit_to_var(&x);
+        if is_lfp {
+            Ok(Mu::Lfp(x, Box::new(mu)))
+        } else {
+            Ok(Mu::Gfp(x, Box::new(mu)))
+        }
+    }
+
+    fn parse_text_act_atom(buff: &mut Buff<char>) -> Result<Act<String>, ParseError> {
+        if buff.eat("¬") || buff.eat("!") {
+            Self::parse_text_act_atom(buff).map(|act| Act::Not(Box::new(act)))
+        } else if buff.eat("(") {
+            let act = Self::parse_text_act_disj(buff)?;
+            expect_token(buff, &[")"])?;
+            Ok(act)
+        } else if buff.eat("{") {
+            let mut actions = vec![Self::parse_ident(buff, false)?];
+            while !buff.eat("}") {
+                actions.push(Self::parse_ident(buff, false)?);
+            }
+            Ok(Act::Set(actions))
+        } else {
+            match Self::parse_ident(buff, false)?.as_str() {
+                "true" => Ok(Act::True),
+                a => Ok(Act::One(a.to_string())),
+            }
+        }
+    }
+
+    fn parse_text_act_conj(buff: &mut Buff<char>) -> Result<Act<String>, ParseError> {
+        let mut lhs = Self::parse_text_act_atom(buff)?;
+        while buff.eat("∧") || buff.eat("&&") {
+            let rhs = Self::parse_text_act_atom(buff)?;
+            lhs = Act::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// Action formula: `true`, an action, a set `{a b ...}`, or the negation `!f`,
+    /// conjunction `f && g` and disjunction `f || g` of action formulas
+    fn parse_text_act_disj(buff: &mut Buff<char>) -> Result<Act<String>, ParseError> {
+        let mut lhs = Self::parse_text_act_conj(buff)?;
+        while buff.eat("∨") || buff.eat("||") {
+            let rhs = Self::parse_text_act_conj(buff)?;
+            lhs = Act::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_text_reg_star(buff: &mut Buff<char>) -> Result<Reg<String>, ParseError> {
+        buff.trim()?;
+        let is_group = buff.top() == Some('(');
+        buff.save();
+        let mut reg = match Self::parse_text_act_disj(buff) {
+            Ok(act) => {
+                buff.commit();
+                Reg::Act(act)
+            }
+            // A parenthesis may also open a regular expression
+            Err(_) if is_group => {
+                buff.restore();
+                buff.pop();
+                let reg = Self::parse_text_reg_alt(buff)?;
+                expect_token(buff, &[")"])?;
+                reg
+            }
+            Err(err) => {
+                buff.restore();
+                return Err(err);
+            }
+        };
+        while buff.eat("*") {
+            reg = Reg::Star(Box::new(reg));
+        }
+        Ok(reg)
+    }
+
+    fn parse_text_reg_seq(buff: &mut Buff<char>) -> Result<Reg<String>, ParseError> {
+        let mut lhs = Self::parse_text_reg_star(buff)?;
+        while buff.eat(".") {
+            let rhs = Self::parse_text_reg_star(buff)?;
+            lhs = Reg::Seq(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// Regular expression: an action formula, or the sequence `R . S`,
+    /// choice `R + S` and repetition `R*` of regular expressions
+    fn parse_text_reg_alt(buff: &mut Buff<char>) -> Result<Reg<String>, ParseError> {
+        let mut lhs = Self::parse_text_reg_seq(buff)?;
+        while buff.eat("+") {
+            let rhs = Self::parse_text_reg_seq(buff)?;
+            lhs = Reg::Alt(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_text_modality(
+        buff: &mut Buff<char>,
+        close: &str,
+        is_ex: bool,
+    ) -> Result<Self, ParseError> {
+        if buff.eat("~") {
+            let act = Self::parse_text_act_disj(buff)?;
+            expect_token(buff, &[close])?;
+            let mu = Box::new(Self::parse_text_atom(buff)?);
+            return Ok(if is_ex {
+                Mu::ExPred(act, mu)
+            } else {
+                Mu::AllPred(act, mu)
+            });
+        }
+        let reg = Self::parse_text_reg_alt(buff)?;
+        expect_token(buff, &[close])?;
+        let mu = Self::parse_text_atom(buff)?;
+        Ok(reg.modality(mu, is_ex))
+    }
+
+    fn parse_text_atom(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        if buff.eat("¬") || buff.eat("!") {
+            Self::parse_text_atom(buff).map(|mu| Mu::Neg(Box::new(mu)))
+        } else if buff.eat("⟨") {
+            Self::parse_text_modality(buff, "⟩", true)
+        } else if buff.eat("<") {
+            Self::parse_text_modality(buff, ">", true)
+        } else if buff.eat("[") {
+            Self::parse_text_modality(buff, "]", false)
+        } else if buff.eat("μ") {
+            Self::parse_text_fixpoint(buff, true)
+        } else if buff.eat("ν") {
+            Self::parse_text_fixpoint(buff, false)
+        } else if buff.eat("(") {
+            let mu = Self::parse_text_disj(buff)?;
+            expect_token(buff, &[")"])?;
+            Ok(mu)
+        } else if buff.top().is_some_and(|c| c.is_alphabetic() || c == '_') {
+            match Self::parse_ident(buff, false)?.as_str() {
+                "mu" => Self::parse_text_fixpoint(buff, true),
+                "nu" => Self::parse_text_fixpoint(buff, false),
+                c @ ("true" | "false") => Ok(Mu::constant(c == "true")),
+                p => Ok(Mu::Lit(p.to_string())),
+            }
+        } else {
+            Err(buff.error("a formula"))
+        }
+    }
+
+    fn parse_text_conj(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        let mut lhs = Self::parse_text_atom(buff)?;
+        while buff.eat("∧") || buff.eat("&&") {
+            let rhs = Self::parse_text_atom(buff)?;
+            lhs = Mu::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_text_disj(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        let mut lhs = Self::parse_text_conj(buff)?;
+        while buff.eat("∨") || buff.eat("||") {
+            let rhs = Self::parse_text_conj(buff)?;
+            lhs = Mu::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// Read a formula in infix syntax, whose first character is located at `start`.
+    ///
+    /// Negations and modalities bind tighter than conjunctions, which bind
+    /// tighter than disjunctions, and the body of a fixpoint extends as far as possible.
+    /// The operators can be written `¬`/`!`, `∧`/`&&`, `∨`/`||`, `⟨a⟩`/`<a>`, `[a]`,
+    /// `μX.`/`mu X.` and `νX.`/`nu X.`. Modalities take regular expressions over
+    /// actions, which are expanded into fixpoints.
+    pub fn from_text(text: &str, start: Pos) -> Result<Self, ParseError> {
+        let (chars, end) = Pos::locate_from(text, start);
+        let mut buff = Buff::located(chars, end, "end of formula");
+        let mu = Self::parse_text_disj(&mut buff)?;
+        buff.trim()?;
         buff.expect_end()?;
-        if is_or {
+        Ok(mu)
+    }
+
+    fn parse_binop(buff: &mut Buff<Sexpr>, is_or: bool) -> Result<Self, ParseError> {
+        let args = buff.convert_list(Self::from_sexpr)?;
+        let res = if is_or {
             args.into_iter()
                 .reduce(|lhs, rhs| Mu::Or(Box::new(lhs), Box::new(rhs)))
         } else {
             args.into_iter()
                 .reduce(|lhs, rhs| Mu::And(Box::new(lhs), Box::new(rhs)))
-        }
+        };
+        Ok(res.unwrap())
     }
 
-    fn parse_neg(buff: &mut Buff<Sexpr>) -> Option<Self> {
-        let mu = buff.next().and_then(Self::from_sexpr)?;
+    fn parse_neg(buff: &mut Buff<Sexpr>) -> Result<Self, ParseError> {
+        let mu = buff.expect_convert(Self::from_sexpr)?;
         buff.expect_end()?;
-        Some(Mu::Neg(Box::new(mu)))
+        Ok(Mu::Neg(Box::new(mu)))
     }
 
     fn lit_to_var(self, var: &String) -> Self {
@@ -162,47 +540,63 @@ impl Mu<String, String> {
             Mu::Lfp(x, lhs) => Mu::Lfp(x.clone(), Box::new(lhs.lit_to_var(var))),
             Mu::Ex(a, lhs) => Mu::Ex(a.clone(), Box::new(lhs.lit_to_var(var))),
             Mu::Var(_) => self,
+            Mu::AllPred(a, lhs) => Mu::AllPred(a.clone(), Box::new(lhs.lit_to_var(var))),
+            Mu::ExPred(a, lhs) => Mu::ExPred(a.clone(), Box::new(lhs.lit_to_var(var))),
         }
     }
 
-    fn parse_fixpoint(buff: &mut Buff<Sexpr>, is_lfp: bool) -> Option<Self> {
-        let var = buff.next()?.get_singleton_opt()?.get_symb_opt()?;
-        let mu = buff.next().and_then(Self::from_sexpr)?.lit_to_var(&var);
+    fn parse_fixpoint(buff: &mut Buff<Sexpr>, is_lfp: bool) -> Result<Self, ParseError> {
+        let var = buff.expect_binder()?;
+        let mu = buff.expect_convert(Self::from_sexpr)?.lit_to_var(&var);
+        buff.expect_end()?;
         if is_lfp {
-            Some(Mu::Lfp(var, Box::new(mu)))
+            Ok(Mu::Lfp(var, Box::new(mu)))
         } else {
-            Some(Mu::Gfp(var, Box::new(mu)))
+            Ok(Mu::Gfp(var, Box::new(mu)))
         }
     }
 
-    fn parse_quantifier(buff: &mut Buff<Sexpr>, is_any: bool) -> Option<Self> {
-        let action = buff.next()?.get_singleton_opt()?.get_symb_opt()?;
-        let mu = buff.next().and_then(Self::from_sexpr)?;
+    fn parse_quantifier(buff: &mut Buff<Sexpr>, is_any: bool) -> Result<Self, ParseError> {
+        let reg = buff.expect_convert(Reg::from_sexpr)?;
+        let mu = buff.expect_convert(Self::from_sexpr)?;
+        buff.expect_end()?;
+        Ok(reg.modality(mu, is_any))
+    }
+
+    fn parse_converse(buff: &mut Buff<Sexpr>, is_any: bool) -> Result<Self, ParseError> {
+        let act = buff.expect_convert(Act::from_sexpr)?;
+        let mu = Box::new(buff.expect_convert(Self::from_sexpr)?);
         buff.expect_end()?;
         if is_any {
-            Some(Mu::Ex(action, Box::new(mu)))
+            Ok(Mu::ExPred(act, mu))
         } else {
-            Some(Mu::All(action, Box::new(mu)))
+            Ok(Mu::AllPred(act, mu))
         }
     }
 
-    pub fn from_sexpr(sexpr: Sexpr) -> Option<Self> {
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
         match sexpr {
-            Sexpr::Sym(s) => Some(Mu::Lit(s)),
-            Sexpr::Num(_) => None,
-            Sexpr::List(list) => {
-                let mut buff = Buff::new(list);
-                let op = buff.expect_cond(Sexpr::is_symb)?.get_symb();
-                if op == *"any" || op == *"all" {
-                    Self::parse_quantifier(&mut buff, op == *"any")
-                } else if op == *"lfp" || op == "gfp" {
-                    Self::parse_fixpoint(&mut buff, op == *"lfp")
-                } else if op == *"or" || op == *"and" {
-                    Self::parse_binop(&mut buff, op == *"or")
-                } else if op == *"not" {
-                    Self::parse_neg(&mut buff)
-                } else {
-                    None
+            Sexpr::Sym(s, _) if s == "true" || s == "false" => Ok(Mu::constant(s == "true")),
+            Sexpr::Sym(s, _) => Ok(Mu::Lit(s)),
+            Sexpr::Str(s, span) => {
+                let start = Pos {
+                    col: span.start.col + 1,
+                    ..span.start
+                };
+                Self::from_text(&s, start)
+            }
+            Sexpr::Num(_, _) => Err(sexpr.error("a μ-calculus formula")),
+            Sexpr::List(_, _) => {
+                let mut buff = sexpr.into_buff("a μ-calculus formula")?;
+                let pos = buff.position();
+                let op = buff.expect_symb()?;
+                match op.as_str() {
+                    "any" | "all" => Self::parse_quantifier(&mut buff, op == "any"),
+                    "any-pred" | "all-pred" => Self::parse_converse(&mut buff, op == "any-pred"),
+                    "lfp" | "gfp" => Self::parse_fixpoint(&mut buff, op == "lfp"),
+                    "or" | "and" => Self::parse_binop(&mut buff, op == "or"),
+                    "not" => Self::parse_neg(&mut buff),
+                    _ => Err(ParseError::new(pos, "a μ-calculus operator", op)),
                 }
             }
         }
@@ -218,14 +612,19 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Mu::Lit(p) => write!(f, "{}", p),
+            Mu::Gfp(x, a) | Mu::Lfp(x, a) if matches!(&**a, Mu::Var(y) if y == x) => {
+                write!(f, "{}", matches!(self, Mu::Gfp(_, _)))
+            }
             Mu::Neg(a) => write!(f, "¬{}", a),
             Mu::And(a, b) => write!(f, "({} ∧ {})", a, b),
             Mu::Or(a, b) => write!(f, "({} ∨ {})", a, b),
-            Mu::Gfp(x, a) => write!(f, "ν {}.({})", x, a),
+            Mu::Gfp(x, a) => write!(f, "(ν {}. {})", x, a),
             Mu::All(act, a) => write!(f, "([{}]{})", act, a),
-            Mu::Lfp(x, a) => write!(f, "μ {}.({})", x, a),
+            Mu::Lfp(x, a) => write!(f, "(μ {}. {})", x, a),
             Mu::Ex(act, a) => write!(f, "(⟨{}⟩{})", act, a),
             Mu::Var(c) => write!(f, "{}", c),
+            Mu::AllPred(act, a) => write!(f, "([~{}]{})", act, a),
+            Mu::ExPred(act, a) => write!(f, "(⟨~{}⟩{})", act, a),
         }
     }
 }
@@ -233,7 +632,6 @@ where
 #[cfg(test)]
 mod test {
     use super::Mu::*;
-    use super::Sexpr::*;
     use super::*;
 
     #[test]
@@ -243,7 +641,7 @@ mod test {
             Lfp(
                 "x".to_string(),
                 Box::new(Or(
-                    Box::new(All('a', Box::new(Var("x".to_string())))),
+                    Box::new(All(Act::One('a'), Box::new(Var("x".to_string())))),
                     Box::new(Var("x".to_string()))
                 ))
             )
@@ -257,7 +655,7 @@ mod test {
             Lfp(
                 "x".to_string(),
                 Box::new(Or(
-                    Box::new(Ex('a', Box::new(Var("x".to_string())))),
+                    Box::new(Ex(Act::One('a'), Box::new(Var("x".to_string())))),
                     Box::new(Var("x".to_string()))
                 ))
             )
@@ -294,10 +692,10 @@ mod test {
 
     #[test]
     fn test_5() {
-        let sexpr = List(vec![
-            Sym("lfp".to_string()),
-            List(vec![Sym("x".to_string())]),
-            Sym("x".to_string()),
+        let sexpr = Sexpr::list(vec![
+            Sexpr::sym("lfp"),
+            Sexpr::list(vec![Sexpr::sym("x")]),
+            Sexpr::sym("x"),
         ]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
@@ -307,10 +705,10 @@ mod test {
 
     #[test]
     fn test_6() {
-        let sexpr = List(vec![
-            Sym("gfp".to_string()),
-            List(vec![Sym("x".to_string())]),
-            Sym("x".to_string()),
+        let sexpr = Sexpr::list(vec![
+            Sexpr::sym("gfp"),
+            Sexpr::list(vec![Sexpr::sym("x")]),
+            Sexpr::sym("x"),
         ]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
@@ -320,11 +718,7 @@ mod test {
 
     #[test]
     fn test_7() {
-        let sexpr = List(vec![
-            Sym("and".to_string()),
-            Sym("x".to_string()),
-            Sym("y".to_string()),
-        ]);
+        let sexpr = Sexpr::list(vec![Sexpr::sym("and"), Sexpr::sym("x"), Sexpr::sym("y")]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
             And(
@@ -336,14 +730,14 @@ mod test {
 
     #[test]
     fn test_8() {
-        let sexpr = List(vec![
-            Sym("and".to_string()),
-            List(vec![
-                Sym("lfp".to_string()),
-                List(vec![Sym("x".to_string())]),
-                Sym("x".to_string()),
+        let sexpr = Sexpr::list(vec![
+            Sexpr::sym("and"),
+            Sexpr::list(vec![
+                Sexpr::sym("lfp"),
+                Sexpr::list(vec![Sexpr::sym("x")]),
+                Sexpr::sym("x"),
             ]),
-            Sym("x".to_string()),
+            Sexpr::sym("x"),
         ]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
@@ -356,27 +750,131 @@ mod test {
 
     #[test]
     fn test_9() {
-        let sexpr = List(vec![
-            Sym("all".to_string()),
-            List(vec![Sym("a".to_string())]),
-            Sym("s1".to_string()),
+        let sexpr = Sexpr::list(vec![
+            Sexpr::sym("all"),
+            Sexpr::list(vec![Sexpr::sym("a")]),
+            Sexpr::sym("s1"),
         ]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
-            All("a".to_string(), Box::new(Lit("s1".to_string()))),
+            All(Act::One("a".to_string()), Box::new(Lit("s1".to_string()))),
         )
     }
 
     #[test]
     fn test_10() {
-        let sexpr = List(vec![
-            Sym("any".to_string()),
-            List(vec![Sym("a".to_string())]),
-            Sym("s1".to_string()),
+        let sexpr = Sexpr::list(vec![
+            Sexpr::sym("any"),
+            Sexpr::list(vec![Sexpr::sym("a")]),
+            Sexpr::sym("s1"),
         ]);
         assert_eq!(
             Mu::from_sexpr(sexpr).unwrap(),
-            Ex("a".to_string(), Box::new(Lit("s1".to_string()))),
+            Ex(Act::One("a".to_string()), Box::new(Lit("s1".to_string()))),
         )
     }
+
+    #[test]
+    fn test_11() {
+        let mu = Mu::from_str("¬μx.(1 ∧ ⟨a⟩x)").unwrap();
+        assert_eq!(mu.to_pnf(), Mu::from_str("νx.(¬1 ∨ [a]x)").unwrap());
+        let mu = Mu::from_str("¬νx.¬(¬x ∨ 2)").unwrap();
+        assert_eq!(mu.to_pnf(), Mu::from_str("μx.(x ∨ 2)").unwrap());
+    }
+
+    #[test]
+    fn test_12() {
+        assert!(Mu::from_str("μx.¬¬x").unwrap().check_monotone().is_ok());
+        assert!(Mu::from_str("μx.¬x").unwrap().check_monotone().is_err());
+        assert!(Mu::from_str("μx.¬νy.(¬x ∧ y)")
+            .unwrap()
+            .check_monotone()
+            .is_ok());
+        assert!(Mu::from_str("μx.νy.(¬y ∧ x)")
+            .unwrap()
+            .check_monotone()
+            .is_err());
+    }
+
+    #[test]
+    fn test_13() {
+        let text = |s: &str| Mu::from_text(s, Pos { line: 1, col: 1 }).unwrap();
+        let mu = text("mu Ready. done || <req_sent>Ready && !busy");
+        assert_eq!(mu, text("μReady.done ∨ (⟨req_sent⟩Ready ∧ ¬busy)"));
+        let Lfp(_, body) = &mu else { panic!() };
+        assert!(matches!(**body, Or(_, _)));
+        let mu = text("nu X. [msg.ack](X && mu Y. p || <a>Y)");
+        assert_eq!(text(&mu.to_string()), mu);
+        assert_eq!(
+            Mu::from_text("mu X. <a X", Pos { line: 1, col: 1 }).unwrap_err(),
+            ParseError::new(Pos { line: 1, col: 10 }, ">", "X")
+        );
+    }
+
+    #[test]
+    fn test_14() {
+        let text = |s: &str| Mu::from_text(s, Pos { line: 1, col: 1 }).unwrap();
+        let mu = text("[!tau]p && <{a b} || c && true>q");
+        let act = Act::Or(
+            Box::new(Act::Set(vec!["a".to_string(), "b".to_string()])),
+            Box::new(Act::And(
+                Box::new(Act::One("c".to_string())),
+                Box::new(Act::True),
+            )),
+        );
+        let And(all, ex) = &mu else { panic!() };
+        assert_eq!(**ex, Ex(act, Box::new(Lit("q".to_string()))));
+        let All(Act::Not(tau), _) = &**all else {
+            panic!()
+        };
+        assert_eq!(**tau, Act::One("tau".to_string()));
+        assert_eq!(text(&mu.to_string()), mu);
+        let sexpr = "(all (not (set tau i)) p)".parse::<Sexpr>().unwrap();
+        assert_eq!(Mu::from_sexpr(sexpr).unwrap(), text("[!{tau i}]p"));
+    }
+
+    #[test]
+    fn test_15() {
+        let text = |s: &str| Mu::from_text(s, Pos { line: 1, col: 1 }).unwrap();
+        let mu = text("[true* . error]false");
+        let sexpr = "(all (seq (star true) error) false)"
+            .parse::<Sexpr>()
+            .unwrap();
+        assert_eq!(Mu::from_sexpr(sexpr).unwrap(), mu);
+        assert_eq!(mu.to_string(), "(ν X1. (([error]false) ∧ ([true]X1)))");
+        assert_eq!(text(&mu.to_string()), mu);
+        // Parentheses group either action formulas or regular expressions
+        assert_eq!(text("<(a || b)*>p"), text("mu X1. p || <a || b>X1"));
+        assert_eq!(text("<(a . b)*>p"), text("mu X1. p || <a><b>X1"));
+    }
+
+    #[test]
+    fn test_16() {
+        let text = |s: &str| Mu::from_text(s, Pos { line: 1, col: 1 }).unwrap();
+        let mu = text("!secret || [~!auth]false");
+        let Or(_, all) = &mu else { panic!() };
+        assert_eq!(
+            **all,
+            AllPred(
+                Act::Not(Box::new(Act::One("auth".to_string()))),
+                Box::new(Mu::constant(false))
+            )
+        );
+        assert_eq!(text(&mu.to_string()), mu);
+        let sexpr = "(any-pred (auth) (all-pred true p))"
+            .parse::<Sexpr>()
+            .unwrap();
+        assert_eq!(Mu::from_sexpr(sexpr).unwrap(), text("<~auth>[~true]p"));
+    }
+
+    #[test]
+    fn test_17() {
+        let text = |s: &str| Mu::from_text(s, Pos { line: 1, col: 1 }).unwrap();
+        let mu = text("<a>true && [b]false");
+        assert_eq!(mu.to_string(), "((⟨a⟩true) ∧ ([b]false))");
+        assert_eq!(text(&mu.to_string()), mu);
+        assert_eq!(text("!true").to_pnf().to_string(), "false");
+        let sexpr = "(and (any a true) (all b false))".parse::<Sexpr>().unwrap();
+        assert_eq!(Mu::from_sexpr(sexpr).unwrap(), mu);
+    }
 }
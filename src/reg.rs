@@ -0,0 +1,210 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    act::Act,
+    buff::{Buff, ParseError},
+    ctl::Fresh,
+    mu::Mu,
+    sexpr::Sexpr,
+};
+
+/// Regular expressions over actions, used in the modalities `[R]φ` and `⟨R⟩φ`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reg<A> {
+    /// A single step through an action matching the formula
+    Act(Act<A>),
+    Seq(Box<Reg<A>>, Box<Reg<A>>),
+    Alt(Box<Reg<A>>, Box<Reg<A>>),
+    /// Any number of repetitions, possibly none
+    Star(Box<Reg<A>>),
+}
+
+/// Names of the variables and propositions occurring in a formula
+fn names<A, P>(mu: &Mu<A, P>, acc: &mut HashSet<String>)
+where
+    A: Display + Clone,
+    P: Display,
+{
+    match mu {
+        Mu::Lit(p) => {
+            acc.insert(p.to_string());
+        }
+        Mu::Var(x) => {
+            acc.insert(x.clone());
+        }
+        Mu::Neg(a) | Mu::All(_, a) | Mu::Ex(_, a) | Mu::AllPred(_, a) | Mu::ExPred(_, a) => {
+            names(a, acc)
+        }
+        Mu::And(a, b) | Mu::Or(a, b) => {
+            names(a, acc);
+            names(b, acc);
+        }
+        Mu::Lfp(x, a) | Mu::Gfp(x, a) => {
+            acc.insert(x.clone());
+            names(a, acc);
+        }
+    }
+}
+
+/// A variable which cannot be captured by a formula
+fn fresh_var<A, P>(mu: &Mu<A, P>) -> String
+where
+    A: Display + Clone,
+    P: Display,
+{
+    let mut used = HashSet::new();
+    names(mu, &mut used);
+    let mut fresh = Fresh(0);
+    loop {
+        let x = fresh.next();
+        if !used.contains(&x) {
+            return x;
+        }
+    }
+}
+
+impl<A> Reg<A>
+where
+    A: Display + Clone,
+{
+    /// One or more repetitions
+    pub fn plus(reg: Reg<A>) -> Self {
+        Reg::Seq(Box::new(reg.clone()), Box::new(Reg::Star(Box::new(reg))))
+    }
+
+    /// The formula `⟨R⟩φ` (if `is_ex`) or `[R]φ`, expressed with
+    /// simple modalities and fixpoints:
+    /// `⟨R.S⟩φ = ⟨R⟩⟨S⟩φ`, `⟨R+S⟩φ = ⟨R⟩φ ∨ ⟨S⟩φ` and `⟨R*⟩φ = μX.φ ∨ ⟨R⟩X`,
+    /// and dually for boxes.
+    pub fn modality<P>(&self, mu: Mu<A, P>, is_ex: bool) -> Mu<A, P>
+    where
+        P: Display + Clone,
+    {
+        match self {
+            Reg::Act(act) if is_ex => Mu::Ex(act.clone(), Box::new(mu)),
+            Reg::Act(act) => Mu::All(act.clone(), Box::new(mu)),
+            Reg::Seq(r, s) => r.modality(s.modality(mu, is_ex), is_ex),
+            Reg::Alt(r, s) => {
+                let lhs = Box::new(r.modality(mu.clone(), is_ex));
+                let rhs = Box::new(s.modality(mu, is_ex));
+                if is_ex {
+                    Mu::Or(lhs, rhs)
+                } else {
+                    Mu::And(lhs, rhs)
+                }
+            }
+            Reg::Star(r) => {
+                let x = fresh_var(&mu);
+                let step = Box::new(r.modality(Mu::Var(x.clone()), is_ex));
+                if is_ex {
+                    Mu::Lfp(x, Box::new(Mu::Or(Box::new(mu), step)))
+                } else {
+                    Mu::Gfp(x, Box::new(Mu::And(Box::new(mu), step)))
+                }
+            }
+        }
+    }
+}
+
+impl Reg<String> {
+    fn parse_list(buff: &mut Buff<Sexpr>, is_seq: bool) -> Result<Self, ParseError> {
+        let args = buff.convert_list(Self::from_sexpr)?;
+        let res = if is_seq {
+            args.into_iter()
+                .reduce(|lhs, rhs| Reg::Seq(Box::new(lhs), Box::new(rhs)))
+        } else {
+            args.into_iter()
+                .reduce(|lhs, rhs| Reg::Alt(Box::new(lhs), Box::new(rhs)))
+        };
+        Ok(res.unwrap())
+    }
+
+    /// Read a regular expression: `(seq r s ...)`, `(alt r s ...)`,
+    /// `(star r)`, `(plus r)` or an action formula
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        let Sexpr::List(list, _) = &sexpr else {
+            return Act::from_sexpr(sexpr).map(Reg::Act);
+        };
+        let op = match list.first() {
+            Some(Sexpr::Sym(op, _)) if list.len() > 1 => op.clone(),
+            _ => return Act::from_sexpr(sexpr).map(Reg::Act),
+        };
+        let mut buff = sexpr.clone().into_buff("a regular expression")?;
+        buff.pop();
+        match op.as_str() {
+            "seq" | "alt" => Self::parse_list(&mut buff, op == "seq"),
+            "star" | "plus" => {
+                let reg = buff.expect_convert(Self::from_sexpr)?;
+                buff.expect_end()?;
+                if op == "star" {
+                    Ok(Reg::Star(Box::new(reg)))
+                } else {
+                    Ok(Reg::plus(reg))
+                }
+            }
+            _ => Act::from_sexpr(sexpr).map(Reg::Act),
+        }
+    }
+}
+
+impl<A> Display for Reg<A>
+where
+    A: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reg::Act(act) => write!(f, "{}", act),
+            Reg::Seq(r, s) => write!(f, "({} . {})", r, s),
+            Reg::Alt(r, s) => write!(f, "({} + {})", r, s),
+            Reg::Star(r) => write!(f, "{}*", r),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{act::Act, mu::Mu, reg::Reg, sexpr::Sexpr, spec::Spec, ts::Ts};
+
+    #[test]
+    fn test_1() {
+        // 1 -send-> 2 -send-> 3 -ack-> 4 (done), 1 -ack-> 5
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3, 4, 5],
+            vec![1],
+            vec![(4, vec!['D'])],
+            vec![
+                (1, vec![('s', 2), ('a', 5)]),
+                (2, vec![('s', 3)]),
+                (3, vec![('a', 4)]),
+            ],
+            vec![],
+        );
+        let send_ack = Reg::Seq(
+            Box::new(Reg::Star(Box::new(Reg::Act(Act::One('s'))))),
+            Box::new(Reg::Act(Act::One('a'))),
+        );
+        let ex = send_ack.modality(Mu::Lit('D'), true);
+        assert_eq!(ts.sat(&ex, HashMap::new()), HashSet::from([1, 2, 3]));
+        let all = send_ack.modality(Mu::Lit('D'), false);
+        assert_eq!(ts.sat(&all, HashMap::new()), HashSet::from([2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_2() {
+        let sexpr = "(gfp (X1) (all (star (alt a (b))) X1))"
+            .parse::<Sexpr>()
+            .unwrap();
+        let mu = Mu::from_sexpr(sexpr).unwrap();
+        // The variable introduced for the star does not capture X1
+        let Mu::Gfp(_, body) = mu else { panic!() };
+        let Mu::Gfp(x, _) = *body else { panic!() };
+        assert_eq!(x, "X2");
+        let sexpr = "(plus (set a b))".parse::<Sexpr>().unwrap();
+        assert_eq!(
+            Reg::from_sexpr(sexpr).unwrap().to_string(),
+            "({a b} . {a b}*)"
+        );
+    }
+}
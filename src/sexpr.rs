@@ -1,29 +1,91 @@
 use std::{fmt::Display, str::FromStr};
 
-use crate::buff::Buff;
+use crate::buff::{Buff, ParseError, Pos};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Location of an s-expression: the position of its first
+/// character and the position right after its last character
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+/// S-expressions.
+/// Spans are ignored when comparing s-expressions
+#[derive(Clone, Debug)]
 pub enum Sexpr {
-    Sym(String),
-    Num(u32),
-    List(Vec<Sexpr>),
+    Sym(String, Span),
+    /// An integer, possibly negative
+    Num(i64, Span),
+    /// A string between double quotes
+    Str(String, Span),
+    List(Vec<Sexpr>, Span),
+}
+
+impl PartialEq for Sexpr {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Sexpr::Sym(x, _), Sexpr::Sym(y, _)) => x == y,
+            (Sexpr::Num(n, _), Sexpr::Num(m, _)) => n == m,
+            (Sexpr::Str(x, _), Sexpr::Str(y, _)) => x == y,
+            (Sexpr::List(l1, _), Sexpr::List(l2, _)) => l1 == l2,
+            _ => false,
+        }
+    }
 }
 
+impl Eq for Sexpr {}
+
 impl Sexpr {
+    /// Build an unlocated symbol
+    pub fn sym(s: &str) -> Self {
+        Sexpr::Sym(s.to_string(), Span::default())
+    }
+
+    /// Build an unlocated number
+    pub fn num(n: i64) -> Self {
+        Sexpr::Num(n, Span::default())
+    }
+
+    /// Build an unlocated string
+    pub fn string(s: &str) -> Self {
+        Sexpr::Str(s.to_string(), Span::default())
+    }
+
+    /// Build an unlocated list
+    pub fn list(l: Vec<Sexpr>) -> Self {
+        Sexpr::List(l, Span::default())
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Sexpr::Sym(_, span)
+            | Sexpr::Num(_, span)
+            | Sexpr::Str(_, span)
+            | Sexpr::List(_, span) => *span,
+        }
+    }
+
+    /// Build an error expecting something else than this s-expression
+    pub fn error<E: Display>(&self, expected: E) -> ParseError {
+        ParseError::new(self.span().start, expected, self)
+    }
+
     pub fn is_symb(&self) -> bool {
-        matches!(self, Sexpr::Sym(_))
+        matches!(self, Sexpr::Sym(_, _))
     }
 
+    /// Check if the s-expression is a natural number (fitting in 32 bits)
     pub fn is_num(&self) -> bool {
-        matches!(self, Sexpr::Num(_))
+        matches!(self, Sexpr::Num(n, _) if u32::try_from(*n).is_ok())
     }
 
     pub fn is_list(&self) -> bool {
-        matches!(self, Sexpr::List(_))
+        matches!(self, Sexpr::List(_, _))
     }
 
     pub fn get_symb(self) -> String {
-        if let Sexpr::Sym(s) = self {
+        if let Sexpr::Sym(s, _) = self {
             s
         } else {
             panic!()
@@ -31,7 +93,7 @@ impl Sexpr {
     }
 
     pub fn get_symb_opt(self) -> Option<String> {
-        if let Sexpr::Sym(s) = self {
+        if let Sexpr::Sym(s, _) = self {
             Some(s)
         } else {
             None
@@ -39,15 +101,19 @@ impl Sexpr {
     }
 
     pub fn get_num(self) -> u32 {
-        if let Sexpr::Num(s) = self {
-            s
+        self.get_num_opt().unwrap()
+    }
+
+    pub fn get_num_opt(self) -> Option<u32> {
+        if let Sexpr::Num(n, _) = self {
+            u32::try_from(n).ok()
         } else {
-            panic!()
+            None
         }
     }
 
-    pub fn get_num_opt(self) -> Option<u32> {
-        if let Sexpr::Num(n) = self {
+    pub fn get_int_opt(self) -> Option<i64> {
+        if let Sexpr::Num(n, _) = self {
             Some(n)
         } else {
             None
@@ -55,7 +121,7 @@ impl Sexpr {
     }
 
     pub fn get_list(self) -> Vec<Self> {
-        if let Sexpr::List(l) = self {
+        if let Sexpr::List(l, _) = self {
             l
         } else {
             panic!()
@@ -63,7 +129,7 @@ impl Sexpr {
     }
 
     pub fn get_list_opt(self) -> Option<Vec<Sexpr>> {
-        if let Sexpr::List(s) = self {
+        if let Sexpr::List(s, _) = self {
             Some(s)
         } else {
             None
@@ -71,7 +137,7 @@ impl Sexpr {
     }
 
     pub fn get_singleton_opt(self) -> Option<Sexpr> {
-        if let Sexpr::List(s) = self {
+        if let Sexpr::List(s, _) = self {
             if s.len() == 1 {
                 Some(s[0].clone())
             } else {
@@ -82,46 +148,136 @@ impl Sexpr {
         }
     }
 
-    fn parse_list(buff: &mut Buff<char>) -> Option<Vec<Self>> {
-        buff.expect_list(Self::parse)
+    /// Buffer of the elements of a list.
+    /// Fails if the s-expression is not a list
+    pub fn into_buff<E: Display>(self, expected: E) -> Result<Buff<Sexpr>, ParseError> {
+        match self {
+            Sexpr::List(list, span) => {
+                let list = list
+                    .into_iter()
+                    .map(|s| {
+                        let pos = s.span().start;
+                        (s, pos)
+                    })
+                    .collect();
+                Ok(Buff::located(list, span.end, "end of list"))
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    fn parse_list(buff: &mut Buff<char>) -> Result<Vec<Self>, ParseError> {
+        let mut list = vec![Self::parse(buff)?];
+        buff.trim()?;
+        while buff.top().is_some_and(|c| c != ')') {
+            list.push(Self::parse(buff)?);
+            buff.trim()?;
+        }
+        Ok(list)
     }
 
-    pub fn parse(buff: &mut Buff<char>) -> Option<Self> {
-        buff.trim();
-        match buff.top()? {
-            '0'..='9' => buff.expect_u32().map(Sexpr::Num),
+    pub fn parse(buff: &mut Buff<char>) -> Result<Self, ParseError> {
+        buff.trim()?;
+        let start = buff.position();
+        let Some(c) = buff.top() else {
+            return Err(buff.error("an s-expression"));
+        };
+        // A minus sign directly followed by a digit starts a negative number
+        let negative = c == '-' && {
+            buff.save();
+            buff.pop();
+            let digit = buff.top().is_some_and(|c| c.is_ascii_digit());
+            buff.restore();
+            digit
+        };
+        match c {
+            '0'..='9' => {
+                let n = buff.expect_u32()?;
+                let end = buff.position();
+                Ok(Sexpr::Num(n as i64, Span { start, end }))
+            }
+            '-' if negative => {
+                buff.pop();
+                let n = buff.expect_u32()?;
+                let end = buff.position();
+                Ok(Sexpr::Num(-(n as i64), Span { start, end }))
+            }
+            '"' => {
+                buff.pop();
+                let mut s = String::new();
+                while let Some(c) = buff.top().filter(|c| *c != '"') {
+                    buff.pop();
+                    s.push(c);
+                }
+                buff.expect('"')?;
+                let end = buff.position();
+                Ok(Sexpr::Str(s, Span { start, end }))
+            }
             '(' => {
                 buff.pop();
                 let list = Self::parse_list(buff)?;
-                buff.trim();
+                buff.trim()?;
                 buff.expect(')')?;
-                Some(Sexpr::List(list))
+                let end = buff.position();
+                Ok(Sexpr::List(list, Span { start, end }))
+            }
+            _ => {
+                let s = buff.expect_symb()?;
+                let end = buff.position();
+                Ok(Sexpr::Sym(s, Span { start, end }))
             }
-            _ => buff.expect_symb().map(Sexpr::Sym),
         }
     }
 }
 
+impl Buff<Sexpr> {
+    pub fn expect_symb(&mut self) -> Result<String, ParseError> {
+        self.expect_cond(Sexpr::is_symb, "a symbol")
+            .map(Sexpr::get_symb)
+    }
+
+    pub fn expect_num(&mut self) -> Result<u32, ParseError> {
+        self.expect_cond(Sexpr::is_num, "a number")
+            .map(Sexpr::get_num)
+    }
+
+    /// Expect an integer, possibly negative
+    pub fn expect_int(&mut self) -> Result<i64, ParseError> {
+        self.expect_cond(|s| matches!(s, Sexpr::Num(_, _)), "an integer")
+            .map(|s| s.get_int_opt().unwrap())
+    }
+
+    /// Expect a symbol between parentheses, as in `(x)`
+    pub fn expect_binder(&mut self) -> Result<String, ParseError> {
+        let sexpr = self.expect_next("a symbol between parentheses")?;
+        sexpr
+            .clone()
+            .get_singleton_opt()
+            .and_then(Sexpr::get_symb_opt)
+            .ok_or_else(|| sexpr.error("a symbol between parentheses"))
+    }
+}
+
 impl FromStr for Sexpr {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Sexpr::parse(&mut Buff::new(s.chars().collect()))
-            .ok_or_else(|| "Sexpr: parsing error".to_string())
+        Sexpr::parse(&mut Buff::from_text(s))
     }
 }
 
 impl Display for Sexpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Sexpr::Sym(x) => write!(f, "{}", x),
-            Sexpr::Num(n) => write!(f, "{}", n),
-            Sexpr::List(l) => {
+            Sexpr::Sym(x, _) => write!(f, "{}", x),
+            Sexpr::Num(n, _) => write!(f, "{}", n),
+            Sexpr::Str(s, _) => write!(f, "\"{}\"", s),
+            Sexpr::List(l, _) => {
                 let ls = l
                     .iter()
                     .map(|s| format!("{}", s))
                     .collect::<Vec<String>>()
-                    .join(", ");
+                    .join(" ");
                 write!(f, "({})", ls)
             }
         }
@@ -130,17 +286,20 @@ impl Display for Sexpr {
 
 #[cfg(test)]
 mod test {
-    use crate::{sexpr::Sexpr, sexpr::Sexpr::*};
+    use crate::{
+        buff::{ParseError, Pos},
+        sexpr::Sexpr,
+    };
 
     #[test]
     fn test_1() {
         assert_eq!(
             "(a b c d)".parse::<Sexpr>().unwrap(),
-            Sexpr::List(vec![
-                Sym("a".to_string()),
-                Sym("b".to_string()),
-                Sym("c".to_string()),
-                Sym("d".to_string())
+            Sexpr::list(vec![
+                Sexpr::sym("a"),
+                Sexpr::sym("b"),
+                Sexpr::sym("c"),
+                Sexpr::sym("d")
             ])
         );
     }
@@ -149,7 +308,12 @@ mod test {
     fn test_2() {
         assert_eq!(
             "(1 2 3 4)".parse::<Sexpr>().unwrap(),
-            Sexpr::List(vec![Num(1), Num(2), Num(3), Num(4),])
+            Sexpr::list(vec![
+                Sexpr::num(1),
+                Sexpr::num(2),
+                Sexpr::num(3),
+                Sexpr::num(4),
+            ])
         );
     }
 
@@ -157,11 +321,11 @@ mod test {
     fn test_3() {
         assert_eq!(
             "(1 a 2 b)".parse::<Sexpr>().unwrap(),
-            Sexpr::List(vec![
-                Num(1),
-                Sym("a".to_string()),
-                Num(2),
-                Sym("b".to_string())
+            Sexpr::list(vec![
+                Sexpr::num(1),
+                Sexpr::sym("a"),
+                Sexpr::num(2),
+                Sexpr::sym("b")
             ])
         );
     }
@@ -190,11 +354,11 @@ mod test {
     fn test_8() {
         assert_eq!(
             "(a1 a2 a3 a4)".parse::<Sexpr>().unwrap(),
-            Sexpr::List(vec![
-                Sym("a1".to_string()),
-                Sym("a2".to_string()),
-                Sym("a3".to_string()),
-                Sym("a4".to_string()),
+            Sexpr::list(vec![
+                Sexpr::sym("a1"),
+                Sexpr::sym("a2"),
+                Sexpr::sym("a3"),
+                Sexpr::sym("a4"),
             ])
         );
     }
@@ -203,19 +367,49 @@ mod test {
     fn test_9() {
         assert_eq!(
             "(a1 (a2 a3 a4))".parse::<Sexpr>().unwrap(),
-            Sexpr::List(vec![
-                Sym("a1".to_string()),
-                List(vec![
-                    Sym("a2".to_string()),
-                    Sym("a3".to_string()),
-                    Sym("a4".to_string()),
-                ])
+            Sexpr::list(vec![
+                Sexpr::sym("a1"),
+                Sexpr::list(vec![Sexpr::sym("a2"), Sexpr::sym("a3"), Sexpr::sym("a4"),])
             ])
         );
     }
 
     #[test]
     fn test_10() {
-        assert_eq!(List(vec![Num(1)]).get_singleton_opt(), Some(Num(1)))
+        assert_eq!(
+            Sexpr::list(vec![Sexpr::num(1)]).get_singleton_opt(),
+            Some(Sexpr::num(1))
+        )
+    }
+
+    #[test]
+    fn test_11() {
+        assert_eq!(
+            "(a\n  (b !))".parse::<Sexpr>(),
+            Err(ParseError::new(Pos { line: 2, col: 6 }, "a symbol", '!'))
+        )
+    }
+
+    #[test]
+    fn test_12() {
+        assert_eq!(
+            "(a b".parse::<Sexpr>(),
+            Err(ParseError::new(
+                Pos { line: 1, col: 5 },
+                ')',
+                "end of input"
+            ))
+        )
+    }
+
+    #[test]
+    fn test_13() {
+        let sexpr = "(spec \"mu X. p || <a>X\")".parse::<Sexpr>().unwrap();
+        assert_eq!(
+            sexpr,
+            Sexpr::list(vec![Sexpr::sym("spec"), Sexpr::string("mu X. p || <a>X")])
+        );
+        assert_eq!(sexpr.to_string(), "(spec \"mu X. p || <a>X\")");
+        assert!("(spec \"p)".parse::<Sexpr>().is_err());
     }
 }
@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use crate::{buff::ParseError, ctl::Ctl, ltl::Ltl, mu::Mu, sexpr::Sexpr};
+
+/// A specification of a transition system,
+/// written in μ-calculus, in CTL or in LTL
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    Mu(Mu<A, P>),
+    Ctl(Ctl<A, P>),
+    Ltl(Ltl<P>),
+}
+
+impl<A, P> From<Mu<A, P>> for Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn from(mu: Mu<A, P>) -> Self {
+        Spec::Mu(mu)
+    }
+}
+
+impl<A, P> From<Ctl<A, P>> for Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn from(ctl: Ctl<A, P>) -> Self {
+        Spec::Ctl(ctl)
+    }
+}
+
+impl<A, P> From<Ltl<P>> for Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn from(ltl: Ltl<P>) -> Self {
+        Spec::Ltl(ltl)
+    }
+}
+
+impl<A, P> Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+    P: Clone,
+{
+    /// The μ-calculus formula expressing the specification,
+    /// if it is not an LTL formula
+    pub fn to_mu(&self) -> Option<Mu<A, P>> {
+        match self {
+            Spec::Mu(mu) => Some(mu.clone()),
+            Spec::Ctl(ctl) => Some(ctl.to_mu()),
+            Spec::Ltl(_) => None,
+        }
+    }
+
+    /// Check if the specification uses next-step operators:
+    /// `EX` and `AX` in CTL, `X` in LTL, or any μ-calculus modality
+    pub fn has_next(&self) -> bool {
+        match self {
+            Spec::Mu(mu) => mu.has_modality(false) || mu.has_modality(true),
+            Spec::Ctl(ctl) => ctl.has_next(),
+            Spec::Ltl(ltl) => ltl.has_next(),
+        }
+    }
+
+    /// Check if the specification uses modalities over predecessors
+    pub fn has_converse(&self) -> bool {
+        matches!(self, Spec::Mu(mu) if mu.has_modality(true))
+    }
+
+    /// The propositions and the actions occurring in the specification
+    pub fn signature(&self) -> (Vec<P>, Vec<A>)
+    where
+        A: Eq,
+    {
+        match (self, self.to_mu()) {
+            (Spec::Ltl(ltl), _) => (ltl.props(), vec![]),
+            (_, mu) => mu.unwrap().signature(),
+        }
+    }
+}
+
+impl Spec<String, String> {
+    /// Read a specification. Formulas using only boolean connectives
+    /// are read as μ-calculus formulas.
+    /// If the specification is neither a μ-calculus, a CTL nor an LTL formula,
+    /// the error of the reading that went the furthest is reported.
+    pub fn from_sexpr(sexpr: Sexpr) -> Result<Self, ParseError> {
+        Mu::from_sexpr(sexpr.clone())
+            .map(Spec::Mu)
+            .or_else(|err| {
+                Ctl::from_sexpr(sexpr.clone())
+                    .map(Spec::Ctl)
+                    .map_err(|other| err.or(other))
+            })
+            .or_else(|err| {
+                Ltl::from_sexpr(sexpr)
+                    .map(Spec::Ltl)
+                    .map_err(|other| err.or(other))
+            })
+    }
+}
+
+impl<A, P> Display for Spec<A, P>
+where
+    A: Display,
+    A: Clone,
+    P: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Spec::Mu(mu) => write!(f, "{}", mu),
+            Spec::Ctl(ctl) => write!(f, "{}", ctl),
+            Spec::Ltl(ltl) => write!(f, "{}", ltl),
+        }
+    }
+}
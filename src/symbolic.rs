@@ -0,0 +1,681 @@
+use std::collections::HashMap;
+
+use crate::{
+    act::Act,
+    bdd::{extend, Bdd, Manager, Word, FALSE, TRUE},
+    fair::Fairness,
+    gcl::{BinOp, Domain, Expr, Rule, Value, VarDecl},
+    lang::{CompileError, Instr},
+    mu::Mu,
+    spec::Spec,
+    ts::Ts,
+};
+
+/// How the states of a symbolic system are encoded
+enum Encoding {
+    /// Valuations of the variables of a guarded-command program
+    Valuations(Vec<VarDecl>),
+    /// Numbers of the states of an explicit system, with their names
+    Numbers(HashMap<u32, String>),
+}
+
+/// The value of an expression in every state: a boolean is a function of
+/// the bits of the current state, and an integer a word of such functions
+enum Term {
+    Bool(Bdd),
+    Int(Word),
+}
+
+/// The value of an expression, and the conditions on the current state
+/// under which its evaluation fails
+struct Eval {
+    value: Term,
+    errors: Vec<(String, Bdd)>,
+}
+
+/// A failure of a rule
+enum Failure {
+    /// An evaluation error
+    Eval(String),
+    /// A value out of the bounds of a variable, given by its word
+    OutOfBounds(String, Word),
+}
+
+/// A specification violated by an initial state
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub spec: Spec<String, String>,
+    /// The name of the state
+    pub state: String,
+}
+
+/// A transition system whose sets of states and transition relations
+/// are represented by BDDs over the bits encoding the states.
+///
+/// The bit `i` of the current state is the BDD variable `2i`, and the
+/// bit `i` of the next state the variable `2i + 1`.
+pub struct Symbolic {
+    bdd: Manager,
+    encoding: Encoding,
+    /// The bits encoding each variable, the least significant first
+    bits: Vec<Vec<u32>>,
+    /// Conjunction of the variables of the current and next states
+    current: Bdd,
+    next: Bdd,
+    states: Bdd,
+    initial: Bdd,
+    props: HashMap<String, Bdd>,
+    /// Transition relation of each action
+    relations: Vec<(String, Bdd)>,
+    spec: Vec<Spec<String, String>>,
+    fairness: Vec<Fairness<String, String>>,
+}
+
+/// Number of bits encoding the values of a domain
+fn width(domain: &Domain) -> u32 {
+    match domain {
+        Domain::Bool => 1,
+        Domain::Int(min, max) => 64 - (max.abs_diff(*min)).leading_zeros(),
+    }
+}
+
+/// The code of a value of a domain
+fn code(domain: &Domain, v: Value) -> u64 {
+    match (domain, v) {
+        (Domain::Int(min, _), Value::Int(n)) => n.abs_diff(*min),
+        (_, Value::Bool(b)) => b as u64,
+        _ => unreachable!(),
+    }
+}
+
+impl Symbolic {
+    /// An empty system whose states are encoded by the given numbers of bits
+    fn new(encoding: Encoding, widths: &[u32]) -> Self {
+        let mut bdd = Manager::default();
+        let mut bits = vec![];
+        let mut n = 0;
+        for width in widths {
+            bits.push((n..n + width).collect::<Vec<_>>());
+            n += width;
+        }
+        let current = bdd.cube(&(0..n).map(|i| 2 * i).collect::<Vec<_>>());
+        let next = bdd.cube(&(0..n).map(|i| 2 * i + 1).collect::<Vec<_>>());
+        Symbolic {
+            bdd,
+            encoding,
+            bits,
+            current,
+            next,
+            states: FALSE,
+            initial: FALSE,
+            props: HashMap::new(),
+            relations: vec![],
+            spec: vec![],
+            fairness: vec![],
+        }
+    }
+
+    /// The states in which a variable has a given code, in the current
+    /// or the next state
+    fn encode(&mut self, var: usize, code: u64, next: bool) -> Bdd {
+        let mut res = TRUE;
+        for (k, bit) in self.bits[var].clone().into_iter().enumerate() {
+            let x = self.bdd.var(2 * bit + next as u32);
+            let literal = if code >> k & 1 == 1 {
+                x
+            } else {
+                self.bdd.not(x)
+            };
+            res = self.bdd.and(res, literal);
+        }
+        res
+    }
+
+    /// The name of a state of a non-empty set
+    fn witness(&self, set: Bdd) -> String {
+        let path = self.bdd.pick(set).unwrap();
+        let codes = self
+            .bits
+            .iter()
+            .map(|bits| {
+                bits.iter()
+                    .enumerate()
+                    .filter(|(_, bit)| path.contains(&(2 * **bit, true)))
+                    .map(|(k, _)| 1 << k)
+                    .sum::<u64>()
+            })
+            .collect::<Vec<_>>();
+        match &self.encoding {
+            Encoding::Valuations(vars) => {
+                let vars = vars
+                    .iter()
+                    .zip(codes)
+                    .map(|(decl, code)| match decl.domain {
+                        Domain::Bool => format!("{}={}", decl.name, code == 1),
+                        Domain::Int(min, _) => format!("{}={}", decl.name, min + code as i64),
+                    })
+                    .collect::<Vec<_>>();
+                format!("({})", vars.join(", "))
+            }
+            Encoding::Numbers(names) => {
+                let x = codes[0] as u32;
+                names.get(&x).cloned().unwrap_or_else(|| x.to_string())
+            }
+        }
+    }
+
+    /// One of the states of a non-empty set
+    fn pick_state(&mut self, set: Bdd) -> Bdd {
+        let path = self.bdd.pick(set).unwrap();
+        let mut state = TRUE;
+        for bit in self.bits.concat() {
+            let x = self.bdd.var(2 * bit);
+            let literal = if path.contains(&(2 * bit, true)) {
+                x
+            } else {
+                self.bdd.not(x)
+            };
+            state = self.bdd.and(state, literal);
+        }
+        state
+    }
+
+    /// The value of a word in a state
+    fn value_in(&mut self, word: &[Bdd], state: Bdd) -> i64 {
+        let bits = word
+            .iter()
+            .map(|x| match self.bdd.and(*x, state) {
+                FALSE => FALSE,
+                _ => TRUE,
+            })
+            .collect::<Vec<_>>();
+        self.bdd.value(&bits).unwrap()
+    }
+
+    /// Evaluate an expression bitwise: integers are computed with
+    /// adders and comparators over the bits of the variables
+    fn eval(&mut self, e: &Expr, vars: &[VarDecl]) -> Eval {
+        let value = match e {
+            Expr::Int(n) => Term::Int(self.bdd.constant(*n)),
+            Expr::Bool(b) => Term::Bool(if *b { TRUE } else { FALSE }),
+            Expr::Var(x) => {
+                let i = vars.iter().position(|decl| decl.name == *x).unwrap();
+                let bits = self.bits[i]
+                    .clone()
+                    .into_iter()
+                    .map(|bit| self.bdd.var(2 * bit))
+                    .collect::<Vec<_>>();
+                match vars[i].domain {
+                    Domain::Bool => Term::Bool(bits[0]),
+                    Domain::Int(min, _) => {
+                        let code = self.bdd.unsigned(&bits);
+                        let min = self.bdd.constant(min);
+                        let value = self.bdd.add(&code, &min);
+                        // Values of the domain fit in 64 bits
+                        Term::Int(self.bdd.fit(&value, 64).0)
+                    }
+                }
+            }
+            Expr::Not(a) => {
+                let mut eval = self.eval(a, vars);
+                eval.value = Term::Bool(self.bdd.not(Self::holds(&eval)));
+                return eval;
+            }
+            Expr::Bin(op, lhs, rhs) => {
+                let lhs = self.eval(lhs, vars);
+                let rhs = self.eval(rhs, vars);
+                let mut errors = [lhs.errors, rhs.errors].concat();
+                let value = match (op, lhs.value, rhs.value) {
+                    (BinOp::And, Term::Bool(a), Term::Bool(b)) => Term::Bool(self.bdd.and(a, b)),
+                    (BinOp::Or, Term::Bool(a), Term::Bool(b)) => Term::Bool(self.bdd.or(a, b)),
+                    (BinOp::Eq, Term::Bool(a), Term::Bool(b)) => Term::Bool(self.bdd.iff(a, b)),
+                    (BinOp::Eq, Term::Int(a), Term::Int(b)) => Term::Bool(self.bdd.eq(&a, &b)),
+                    (BinOp::Lt, Term::Int(a), Term::Int(b)) => Term::Bool(self.bdd.lt(&a, &b)),
+                    (BinOp::Gt, Term::Int(a), Term::Int(b)) => Term::Bool(self.bdd.lt(&b, &a)),
+                    (BinOp::Le, Term::Int(a), Term::Int(b)) => {
+                        let gt = self.bdd.lt(&b, &a);
+                        Term::Bool(self.bdd.not(gt))
+                    }
+                    (BinOp::Ge, Term::Int(a), Term::Int(b)) => {
+                        let lt = self.bdd.lt(&a, &b);
+                        Term::Bool(self.bdd.not(lt))
+                    }
+                    (op, Term::Int(a), Term::Int(b)) => {
+                        let res = match op {
+                            BinOp::Add => self.bdd.add(&a, &b),
+                            BinOp::Sub => self.bdd.sub(&a, &b),
+                            BinOp::Mul => self.bdd.mul(&a, &b),
+                            BinOp::Div => self.bdd.div(&a, &b),
+                            _ => unreachable!(),
+                        };
+                        // Values are 64-bit integers, as in explicit evaluation
+                        let (res, mut failure) = self.bdd.fit(&res, 64);
+                        if *op == BinOp::Div {
+                            let zero = self.bdd.constant(0);
+                            let by_zero = self.bdd.eq(&b, &zero);
+                            failure = self.bdd.or(failure, by_zero);
+                        }
+                        if failure != FALSE {
+                            errors.push((op.failure(e), failure));
+                        }
+                        Term::Int(res)
+                    }
+                    _ => unreachable!(),
+                };
+                return Eval { value, errors };
+            }
+        };
+        Eval {
+            value,
+            errors: vec![],
+        }
+    }
+
+    /// The states in which a boolean expression holds
+    fn holds(eval: &Eval) -> Bdd {
+        match eval.value {
+            Term::Bool(holds) => holds,
+            Term::Int(_) => unreachable!(),
+        }
+    }
+
+    /// The transition relation of a rule, and the conditions under which it fails
+    fn relation(&mut self, rule: &Rule, vars: &[VarDecl]) -> (Bdd, Vec<(Failure, Bdd)>) {
+        let guard = self.eval(&rule.guard, vars);
+        let enabled = Self::holds(&guard);
+        let mut relation = enabled;
+        let mut failures = vec![];
+        for (i, decl) in vars.iter().enumerate() {
+            let Some((_, e)) = rule.updates.iter().find(|(x, _)| *x == decl.name) else {
+                for bit in self.bits[i].clone() {
+                    let (x, y) = (self.bdd.var(2 * bit), self.bdd.var(2 * bit + 1));
+                    let frame = self.bdd.iff(x, y);
+                    relation = self.bdd.and(relation, frame);
+                }
+                continue;
+            };
+            let eval = self.eval(e, vars);
+            let next = self.bits[i]
+                .clone()
+                .into_iter()
+                .map(|bit| self.bdd.var(2 * bit + 1))
+                .collect::<Vec<_>>();
+            let update = match (decl.domain, &eval.value) {
+                (Domain::Bool, Term::Bool(value)) => self.bdd.iff(next[0], *value),
+                (Domain::Int(min, max), Term::Int(value)) => {
+                    let (min, max) = (self.bdd.constant(min), self.bdd.constant(max));
+                    let below = self.bdd.lt(value, &min);
+                    let above = self.bdd.lt(&max, value);
+                    let out = self.bdd.or(below, above);
+                    if out != FALSE {
+                        failures
+                            .push((Failure::OutOfBounds(decl.name.clone(), value.clone()), out));
+                    }
+                    let code = self.bdd.sub(value, &min);
+                    let code = extend(&code, next.len());
+                    let mut update = self.bdd.not(out);
+                    for (y, x) in next.iter().zip(code) {
+                        let bit = self.bdd.iff(*y, x);
+                        update = self.bdd.and(update, bit);
+                    }
+                    update
+                }
+                _ => unreachable!(),
+            };
+            failures.extend(
+                eval.errors
+                    .into_iter()
+                    .map(|(err, cond)| (Failure::Eval(err), cond)),
+            );
+            relation = self.bdd.and(relation, update);
+        }
+        // Updates are only evaluated when the guard holds
+        let mut errors = guard
+            .errors
+            .into_iter()
+            .map(|(err, cond)| (Failure::Eval(err), cond))
+            .collect::<Vec<_>>();
+        for (failure, cond) in failures {
+            errors.push((failure, self.bdd.and(cond, enabled)));
+        }
+        (relation, errors)
+    }
+
+    /// Encode a guarded-command program, without enumerating its states.
+    /// As when its states are enumerated, evaluation errors are reported
+    /// when they occur in a reachable state.
+    pub fn from_gcl(
+        vars: Vec<VarDecl>,
+        rules: &[Rule],
+        definitions: &[(String, Expr)],
+        spec: Vec<Spec<String, String>>,
+        fairness: Vec<Fairness<String, String>>,
+    ) -> Result<Self, CompileError> {
+        let widths = vars
+            .iter()
+            .map(|decl| width(&decl.domain))
+            .collect::<Vec<_>>();
+        let mut sys = Symbolic::new(Encoding::Valuations(vars.clone()), &widths);
+        sys.spec = spec;
+        sys.fairness = fairness;
+        sys.initial = TRUE;
+        for (i, decl) in vars.iter().enumerate() {
+            let init = sys.encode(i, code(&decl.domain, decl.init), false);
+            sys.initial = sys.bdd.and(sys.initial, init);
+        }
+        let mut errors = vec![];
+        let mut relations = HashMap::<String, Bdd>::new();
+        for rule in rules {
+            let (relation, failures) = sys.relation(rule, &vars);
+            let before = relations.get(&rule.action).cloned().unwrap_or(FALSE);
+            relations.insert(rule.action.clone(), sys.bdd.or(before, relation));
+            errors.extend(
+                failures
+                    .into_iter()
+                    .map(|(err, cond)| (err, cond, Instr::Rule(rule.clone()))),
+            );
+        }
+        sys.relations = relations.into_iter().collect();
+        sys.states = sys.reachable();
+        for (p, e) in definitions {
+            let eval = sys.eval(e, &vars);
+            let holds = Self::holds(&eval);
+            let prop = sys.bdd.and(holds, sys.states);
+            sys.props.insert(p.clone(), prop);
+            errors.extend(
+                eval.errors.into_iter().map(|(err, cond)| {
+                    (Failure::Eval(err), cond, Instr::Prop(p.clone(), e.clone()))
+                }),
+            );
+        }
+        for (failure, cond, instr) in errors {
+            let reached = sys.bdd.and(cond, sys.states);
+            if reached != FALSE {
+                let state = sys.pick_state(reached);
+                let err = match failure {
+                    Failure::Eval(err) => err,
+                    Failure::OutOfBounds(x, value) => format!(
+                        "the value {} of {} is out of its bounds",
+                        sys.value_in(&value, state),
+                        x
+                    ),
+                };
+                let err = format!("{} in state {}", err, sys.witness(state));
+                return Err(CompileError::EvalError(err, instr));
+            }
+        }
+        Ok(sys)
+    }
+
+    /// Encode an explicit system, its states being encoded by their numbers
+    pub fn from_ts(ts: &Ts<String, String>) -> Self {
+        let max = ts.states.iter().max().cloned().unwrap_or(0);
+        let names = ts.states.iter().map(|x| (*x, ts.state_name(*x))).collect();
+        let mut sys = Symbolic::new(Encoding::Numbers(names), &[32 - max.leading_zeros()]);
+        sys.spec = ts.spec.clone();
+        sys.fairness = ts.fairness.clone();
+        let mut cubes = HashMap::new();
+        for x in ts.states.iter() {
+            let cube = sys.encode(0, *x as u64, false);
+            cubes.insert(*x, cube);
+            sys.states = sys.bdd.or(sys.states, cube);
+        }
+        for x in ts.initial.iter() {
+            sys.initial = sys.bdd.or(sys.initial, cubes[x]);
+        }
+        for (p, set) in ts.labels.iter() {
+            let mut prop = FALSE;
+            for x in ts.transitions.to_states(set) {
+                prop = sys.bdd.or(prop, cubes[&x]);
+            }
+            sys.props.insert(p.clone(), prop);
+        }
+        let mut relations = HashMap::<String, Bdd>::new();
+        for (x, a, y) in ts.transitions.iter() {
+            let relation = relations.get(a).cloned().unwrap_or(FALSE);
+            let next = sys.encode(0, *y as u64, true);
+            let step = sys.bdd.and(cubes[x], next);
+            let relation = sys.bdd.or(relation, step);
+            relations.insert(a.clone(), relation);
+        }
+        sys.relations = relations.into_iter().collect();
+        sys
+    }
+
+    /// The union of the relations of the actions matching an action formula
+    fn matching(&mut self, act: &Act<String>) -> Bdd {
+        let relations = self
+            .relations
+            .iter()
+            .filter(|(a, _)| act.matches(a))
+            .map(|(_, relation)| *relation)
+            .collect::<Vec<_>>();
+        relations
+            .into_iter()
+            .fold(FALSE, |acc, relation| self.bdd.or(acc, relation))
+    }
+
+    /// The successors of a set of states through a relation
+    fn image(&mut self, relation: Bdd, set: Bdd) -> Bdd {
+        let next = self.bdd.and_exists(relation, set, self.current);
+        self.bdd.rename(next, &|var| var - 1)
+    }
+
+    /// The predecessors of a set of states through a relation
+    fn preimage(&mut self, relation: Bdd, set: Bdd) -> Bdd {
+        let next = self.bdd.rename(set, &|var| var + 1);
+        self.bdd.and_exists(relation, next, self.next)
+    }
+
+    /// The states reachable from the initial ones
+    fn reachable(&mut self) -> Bdd {
+        let all = self.matching(&Act::True);
+        let mut reach = self.initial;
+        let mut frontier = self.initial;
+        while frontier != FALSE {
+            let image = self.image(all, frontier);
+            let not_reach = self.bdd.not(reach);
+            frontier = self.bdd.and(image, not_reach);
+            reach = self.bdd.or(reach, frontier);
+        }
+        reach
+    }
+
+    /// Number of states of a set
+    pub fn count(&self, set: Bdd) -> f64 {
+        let n = self.bits.iter().map(Vec::len).sum::<usize>() as u32;
+        self.bdd.count(set, 2 * n) / 2f64.powi(n as i32)
+    }
+
+    /// Number of states of the system
+    pub fn state_count(&self) -> f64 {
+        self.count(self.states)
+    }
+
+    /// The states which have a successor in `set` through the actions
+    /// matching an action formula, or a predecessor if `converse`
+    fn ex(&mut self, act: &Act<String>, set: Bdd, converse: bool) -> Bdd {
+        let relation = self.matching(act);
+        let res = if converse {
+            self.image(relation, set)
+        } else {
+            self.preimage(relation, set)
+        };
+        self.bdd.and(res, self.states)
+    }
+
+    fn complement(&mut self, set: Bdd) -> Bdd {
+        let not = self.bdd.not(set);
+        self.bdd.and(not, self.states)
+    }
+
+    /// Compute the set of states satisfying a closed formula,
+    /// modalities being computed with relational products
+    pub fn sat(&mut self, spec: &Mu<String, String>) -> Bdd {
+        self.sat_el(spec, &mut HashMap::new())
+    }
+
+    fn sat_el(&mut self, spec: &Mu<String, String>, env: &mut HashMap<String, Bdd>) -> Bdd {
+        match spec {
+            Mu::Lit(p) => self.props.get(p).cloned().unwrap_or(FALSE),
+            Mu::Var(x) => env[x],
+            Mu::Neg(a) => {
+                let sat_a = self.sat_el(a, env);
+                self.complement(sat_a)
+            }
+            Mu::And(a, b) => {
+                let sat_a = self.sat_el(a, env);
+                let sat_b = self.sat_el(b, env);
+                self.bdd.and(sat_a, sat_b)
+            }
+            Mu::Or(a, b) => {
+                let sat_a = self.sat_el(a, env);
+                let sat_b = self.sat_el(b, env);
+                self.bdd.or(sat_a, sat_b)
+            }
+            Mu::Lfp(x, a) | Mu::Gfp(x, a) => {
+                let mut sat = match spec {
+                    Mu::Lfp(_, _) => FALSE,
+                    _ => self.states,
+                };
+                let outer = env.remove(x);
+                loop {
+                    env.insert(x.clone(), sat);
+                    let sat_next = self.sat_el(a, env);
+                    if sat_next == sat {
+                        break;
+                    }
+                    sat = sat_next;
+                }
+                match outer {
+                    Some(outer) => env.insert(x.clone(), outer),
+                    None => env.remove(x),
+                };
+                sat
+            }
+            Mu::Ex(act, a) | Mu::ExPred(act, a) => {
+                let sat_a = self.sat_el(a, env);
+                self.ex(act, sat_a, matches!(spec, Mu::ExPred(_, _)))
+            }
+            Mu::All(act, a) | Mu::AllPred(act, a) => {
+                let sat_a = self.sat_el(a, env);
+                let not_a = self.complement(sat_a);
+                let ex = self.ex(act, not_a, matches!(spec, Mu::AllPred(_, _)));
+                self.complement(ex)
+            }
+        }
+    }
+
+    /// The specifications violated by an initial state, with such a state.
+    /// CTL specifications are translated into μ-calculus formulas, LTL
+    /// specifications and fairness constraints are not supported.
+    pub fn violations(&mut self) -> Result<Vec<Violation>, String> {
+        if let Some(constraint) = self.fairness.first() {
+            return Err(format!(
+                "the fairness constraint {} is not supported by the symbolic backend",
+                constraint
+            ));
+        }
+        let mut violations = vec![];
+        for spec in self.spec.clone() {
+            let mu = spec.to_mu().ok_or_else(|| {
+                format!(
+                    "the LTL specification {} is not supported by the symbolic backend",
+                    spec
+                )
+            })?;
+            let sat = self.sat(&mu);
+            let unsat = self.bdd.not(sat);
+            let bad = self.bdd.and(self.initial, unsat);
+            if bad != FALSE {
+                let state = self.witness(bad);
+                violations.push(Violation { spec, state });
+            }
+        }
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buff::Pos,
+        lang::{CompileError, Prog},
+        mu::Mu,
+        symbolic::Symbolic,
+    };
+
+    const PROG: &str = "(int x 0 5 0)(bool b false)
+        (rule inc (< x 5) (set x (+ x 1)))
+        (rule flip (= x 5) (set b (not b)) (set x (/ x 2)))
+        (prop Done b)
+        (prop Zero (= x 0))
+        (spec (ag (ef Done)))
+        (spec (ef (and Done Zero)))";
+
+    #[test]
+    fn test_1() {
+        let ts = PROG.parse::<Prog>().unwrap().compile().unwrap();
+        let mut sys = PROG.parse::<Prog>().unwrap().compile_symbolic().unwrap();
+        let mut explicit = Symbolic::from_ts(&ts);
+        assert_eq!(sys.state_count(), 10.0);
+        assert_eq!(explicit.state_count(), 10.0);
+        for spec in ts.spec.iter() {
+            let mu = spec.to_mu().unwrap();
+            let sat = ts.sat(&mu, Default::default()).len() as f64;
+            let sat_sys = sys.sat(&mu);
+            assert_eq!(sys.count(sat_sys), sat);
+            let sat_explicit = explicit.sat(&mu);
+            assert_eq!(explicit.count(sat_explicit), sat);
+        }
+        let violations = sys.violations().unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].state, "(x=0, b=false)");
+        assert_eq!(explicit.violations(), Ok(violations));
+        // The states reachable from a Done state
+        let reach = Mu::from_text("mu X. Done || <~true>X", Pos { line: 1, col: 1 }).unwrap();
+        let sat = sys.sat(&reach);
+        assert_eq!(sys.count(sat), 8.0);
+    }
+
+    #[test]
+    fn test_2() {
+        let prog = "(int x 0 3 0)(rule inc true (set x (+ x 1)))";
+        let errors = prog.parse::<Prog>().unwrap().compile_symbolic().err();
+        let explicit = prog.parse::<Prog>().unwrap().compile().err();
+        assert!(matches!(
+            errors.as_deref(),
+            Some([CompileError::EvalError(_, _)])
+        ));
+        assert_eq!(errors, explicit);
+        let prog = "(int x -3 3 3)(int y 0 2 2)(rule dec true (set x (- x 1)) (set y (/ 6 y)))";
+        let errors = prog.parse::<Prog>().unwrap().compile_symbolic().err();
+        let explicit = prog.parse::<Prog>().unwrap().compile().err();
+        assert!(errors.is_some());
+        assert_eq!(errors, explicit);
+    }
+
+    #[test]
+    fn test_3() {
+        // Arithmetic over negative ranges is encoded bitwise
+        let prog = "(int x -4 4 -4)(int y -3 5 1)
+            (rule step (< (* x y) 12) (set x (- (* 2 (/ x 3)) (/ y -2))) (set y (+ x 1)))
+            (rule down (>= (+ x y) 0) (set y (- (- y (* y y (/ 1 2))) 1)))
+            (prop Eq (= (* x -1) y))
+            (prop Low (<= (- x y) -3))
+            (spec (ag (ef Eq)))
+            (spec (ef Low))
+            (spec (ag (or Eq (not Low))))";
+        let ts = prog.parse::<Prog>().unwrap().compile().unwrap();
+        let mut sys = prog.parse::<Prog>().unwrap().compile_symbolic().unwrap();
+        assert_eq!(sys.state_count(), ts.states.len() as f64);
+        for spec in ts.spec.iter() {
+            let mu = spec.to_mu().unwrap();
+            let sat = ts.sat(&mu, Default::default()).len() as f64;
+            let sat_sys = sys.sat(&mu);
+            assert_eq!(sys.count(sat_sys), sat);
+        }
+        let violations = sys.violations().unwrap();
+        assert_eq!(violations.len(), ts.violations().len());
+    }
+}
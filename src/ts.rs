@@ -4,9 +4,21 @@ use std::{
     hash::Hash,
 };
 
-use crate::mu::Mu;
+use crate::{
+    act::Act, bits::BitSet, ctl::Ctl, dense::Transitions, fair::Fairness, mu::Mu, spec::Spec,
+};
+
+/// Algorithms evaluating μ-calculus formulas
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Backend {
+    /// Kleene iteration of fixpoints
+    #[default]
+    Fixpoint,
+    /// Solution of the verification game with Zielonka's algorithm
+    Game,
+}
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Ts<A, P>
 where
     A: Display + Eq + Clone + Hash,
@@ -14,9 +26,14 @@ where
 {
     pub(crate) states: HashSet<u32>,
     pub(crate) initial: HashSet<u32>,
-    pub(crate) labels: HashMap<u32, HashSet<P>>,
-    pub(crate) transitions: HashMap<u32, HashMap<A, u32>>,
-    pub(crate) spec: Vec<Mu<A, P>>,
+    /// The states labelled by each proposition, by their index in `transitions`
+    pub(crate) labels: HashMap<P, BitSet>,
+    pub(crate) transitions: Transitions<A>,
+    pub(crate) spec: Vec<Spec<A, P>>,
+    /// Constraints restricting the paths considered by path quantifiers
+    pub(crate) fairness: Vec<Fairness<A, P>>,
+    /// Names of the states declared by name
+    pub(crate) names: HashMap<u32, String>,
 }
 
 impl<A, P> Ts<A, P>
@@ -24,162 +41,412 @@ where
     A: Display + Eq + Clone + Hash,
     P: Eq + Display + Clone + Hash,
 {
-    pub fn new(
+    pub fn new<S>(
         states: Vec<u32>,
         initials: Vec<u32>,
         labels: Vec<(u32, Vec<P>)>,
         transitions: Vec<(u32, Vec<(A, u32)>)>,
-        spec: Vec<Mu<A, P>>,
-    ) -> Self {
+        spec: Vec<S>,
+    ) -> Self
+    where
+        S: Into<Spec<A, P>>,
+    {
+        let states = states.into_iter().collect();
+        let transitions = transitions
+            .into_iter()
+            .flat_map(|(s, post)| post.into_iter().map(move |(a, t)| (s, a, t)));
+        let transitions = Transitions::new(&states, transitions);
         Ts {
-            states: states.into_iter().collect(),
+            labels: transitions.to_label_bits(
+                labels
+                    .into_iter()
+                    .map(|(s, labels)| (s, HashSet::from_iter(labels))),
+            ),
+            transitions,
+            states,
             initial: initials.into_iter().collect(),
-            labels: labels
-                .into_iter()
-                .map(|(s, labels)| (s, HashSet::from_iter(labels.into_iter())))
-                .collect(),
-            transitions: transitions
-                .into_iter()
-                .map(|(s, post)| (s, HashMap::from_iter(post.into_iter())))
-                .collect(),
-            spec,
+            spec: spec.into_iter().map(Into::into).collect(),
+            fairness: vec![],
+            names: HashMap::new(),
         }
     }
 
+    /// The name of a state, or its number if it has no name
+    pub fn state_name(&self, x: u32) -> String {
+        self.names.get(&x).cloned().unwrap_or_else(|| x.to_string())
+    }
+
     pub fn label(&self, x: &u32) -> HashSet<P> {
-        self.labels.get(x).unwrap_or(&HashSet::new()).clone()
+        self.labels
+            .keys()
+            .filter(|p| self.has_label(x, p))
+            .cloned()
+            .collect()
+    }
+
+    /// Check if a state is labelled by a proposition
+    pub(crate) fn has_label(&self, x: &u32, p: &P) -> bool {
+        let set = self.labels.get(p);
+        self.transitions
+            .index(x)
+            .is_some_and(|i| set.is_some_and(|set| set.contains(i)))
+    }
+
+    /// The successors of a state through a given action
+    pub fn succ<'a>(&'a self, x: &u32, act: &A) -> impl Iterator<Item = &'a u32> + 'a {
+        self.transitions.succ(x, act)
+    }
+
+    /// The set of actions labelling the transitions of the system
+    pub fn actions(&self) -> Vec<A> {
+        self.transitions.actions().to_vec()
+    }
+
+    /// Successors of a state through the actions matching an action formula
+    pub fn succ_matching<'a>(
+        &'a self,
+        x: &u32,
+        act: &'a Act<A>,
+    ) -> impl Iterator<Item = &'a u32> + 'a {
+        self.transitions
+            .post(x)
+            .filter(move |(a, _)| act.matches(a))
+            .map(|(_, y)| y)
+    }
+
+    /// Predecessors of a state through the actions matching an action formula
+    pub fn pred_matching<'a>(
+        &'a self,
+        x: &u32,
+        act: &'a Act<A>,
+    ) -> impl Iterator<Item = &'a u32> + 'a {
+        self.transitions
+            .pre(x)
+            .filter(move |(a, _)| act.matches(a))
+            .map(|(_, x)| x)
+    }
+
+    /// Successors of a state through the actions matching an action formula,
+    /// or its predecessors if `converse`
+    pub(crate) fn adjacent<'a>(
+        &'a self,
+        x: &u32,
+        act: &'a Act<A>,
+        converse: bool,
+    ) -> Box<dyn Iterator<Item = &'a u32> + 'a> {
+        if converse {
+            Box::new(self.pred_matching(x, act))
+        } else {
+            Box::new(self.succ_matching(x, act))
+        }
+    }
+
+    /// All the successors of a state, whatever the action
+    pub fn post<'a>(&'a self, x: &u32) -> impl Iterator<Item = &'a u32> + 'a {
+        self.transitions.post(x).map(|(_, y)| y)
     }
 
-    pub fn succ(&self, x: &u32, act: &A) -> Option<&u32> {
-        self.transitions.get(x).and_then(|succ| succ.get(act))
+    /// Check if a state has no successor
+    pub fn is_deadlock(&self, x: &u32) -> bool {
+        self.post(x).next().is_none()
     }
 
     pub fn check(&self) -> bool {
+        self.check_with(Backend::default())
+    }
+
+    /// Check the specification using a given backend
+    pub fn check_with(&self, backend: Backend) -> bool {
         self.spec.iter().all(|form| {
-            let sat = self.sat(form, HashMap::new());
+            let sat = self.sat_spec_with(form, backend);
             self.initial.iter().all(|s| sat.contains(s))
         })
     }
 
-    pub fn sat(&self, spec: &Mu<A, P>, env: HashMap<String, HashSet<u32>>) -> HashSet<u32> {
-        match spec {
-            Mu::Lit(p) => {
-                let s = self
-                    .states
-                    .clone()
-                    .into_iter()
-                    .filter(|x| self.label(x).contains(p))
-                    .collect();
-                self.states.intersection(&s).cloned().collect()
+    /// Compute the set of states satisfying a specification.
+    /// CTL specifications are checked with the dedicated labelling algorithm.
+    pub fn sat_spec(&self, spec: &Spec<A, P>) -> HashSet<u32> {
+        self.sat_spec_with(spec, Backend::default())
+    }
+
+    /// Compute the set of states satisfying a specification using a given backend.
+    /// With the game backend, CTL specifications are translated into μ-calculus.
+    /// LTL specifications are always checked with automata.
+    ///
+    /// Under fairness constraints, path quantifiers only range over fair paths,
+    /// CTL specifications are always checked with the labelling algorithm,
+    /// and the modalities of μ-calculus specifications only range over the
+    /// states from which a fair path starts.
+    pub fn sat_spec_with(&self, spec: &Spec<A, P>, backend: Backend) -> HashSet<u32> {
+        match (spec, backend) {
+            (Spec::Mu(_), _) if !self.fairness.is_empty() => {
+                self.fair_restriction().sat_spec_with(spec, backend)
             }
-            Mu::Neg(a) => {
-                let s2 = self.sat(a, env).iter().cloned().collect();
-                self.states.difference(&s2).cloned().collect()
+            (Spec::Mu(mu), Backend::Fixpoint) => self.sat(mu, HashMap::new()),
+            (Spec::Ctl(ctl), Backend::Game) if self.fairness.is_empty() => {
+                self.sat_game(&ctl.to_mu())
             }
-            Mu::And(a, b) => {
-                let sat_a = self.sat(a, env.clone());
-                let sat_b = self.sat(b, env);
+            (Spec::Ctl(ctl), _) => self.sat_ctl(ctl),
+            (Spec::Mu(mu), Backend::Game) => self.sat_game(mu),
+            (Spec::Ltl(ltl), _) => self.sat_ltl(ltl),
+        }
+    }
+
+    /// States of `within` from which a state of `target`
+    /// can be reached by staying in `within`
+    fn backward_reach(&self, target: HashSet<u32>, within: &HashSet<u32>) -> HashSet<u32> {
+        let mut todo: Vec<u32> = target.iter().cloned().collect();
+        let mut reach = target;
+        while let Some(y) = todo.pop() {
+            for x in self.pred_matching(&y, &Act::True) {
+                if within.contains(x) && reach.insert(*x) {
+                    todo.push(*x);
+                }
+            }
+        }
+        reach
+    }
+
+    /// States from which there is a (fair) maximal path staying in `within`
+    pub(crate) fn sat_eg(&self, within: &HashSet<u32>) -> HashSet<u32> {
+        let target = self
+            .fair_components(self, within, &[])
+            .into_iter()
+            .flatten()
+            .collect();
+        self.backward_reach(target, within)
+    }
+
+    /// Compute the set of states satisfying a CTL formula with the
+    /// labelling algorithm: backward reachability for until operators and
+    /// strongly connected components for `EG`.
+    /// Path quantifiers range over maximal paths, as in [`Ctl::to_mu`],
+    /// which satisfy the fairness constraints.
+    pub fn sat_ctl(&self, spec: &Ctl<A, P>) -> HashSet<u32> {
+        match spec {
+            Ctl::Lit(p) => self
+                .labels
+                .get(p)
+                .map_or_else(HashSet::new, |set| self.transitions.to_states(set)),
+            Ctl::Neg(a) => {
+                let sat_a = self.sat_ctl(a);
+                self.states.difference(&sat_a).cloned().collect()
+            }
+            Ctl::And(a, b) => {
+                let sat_a = self.sat_ctl(a);
+                let sat_b = self.sat_ctl(b);
                 sat_a.intersection(&sat_b).cloned().collect()
             }
-            Mu::Or(a, b) => {
-                let sat_a = self.sat(a, env.clone());
-                let sat_b = self.sat(b, env);
+            Ctl::Or(a, b) => {
+                let sat_a = self.sat_ctl(a);
+                let sat_b = self.sat_ctl(b);
                 sat_a.union(&sat_b).cloned().collect()
             }
-            Mu::Gfp(x, a) => {
-                let mut sat = self.states.clone();
-                loop {
-                    let mut env_next = env.clone();
-                    env_next.insert(x.clone(), sat.clone());
-                    let sat_next = self.sat(a, env_next);
-                    if sat_next == sat {
-                        break;
-                    }
-                    sat = sat_next;
-                }
-                sat
+            Ctl::Ex(act, a) => {
+                let sat_a = self.fair(self.sat_ctl(a));
+                self.states
+                    .iter()
+                    .filter(|x| match act {
+                        Some(act) => self.succ(x, act).any(|y| sat_a.contains(y)),
+                        None => self.post(x).any(|y| sat_a.contains(y)),
+                    })
+                    .cloned()
+                    .collect()
             }
-            Mu::All(act, a) => {
-                let sat_a = self.sat(a, env);
-                let mut sat_all = HashSet::<u32>::new();
-                for s1 in &self.states {
-                    if self.succ(s1, act).iter().all(|s2| sat_a.contains(s2)) {
-                        sat_all.insert(*s1);
-                    }
-                }
-                sat_all
+            Ctl::Ax(act, a) => {
+                let sat_a = self.sat_ctl(a);
+                let not_a = self.fair(self.states.difference(&sat_a).cloned().collect());
+                self.states
+                    .iter()
+                    .filter(|x| match act {
+                        Some(act) => !self.succ(x, act).any(|y| not_a.contains(y)),
+                        None => !self.post(x).any(|y| not_a.contains(y)),
+                    })
+                    .cloned()
+                    .collect()
             }
-            Mu::Lfp(x, a) => {
-                let mut sat = HashSet::new();
-                loop {
-                    let mut env_next = env.clone();
-                    env_next.insert(x.clone(), sat.clone());
-                    let sat_next = self.sat(a, env_next);
-                    if sat_next == sat {
-                        break;
-                    }
-                    sat = sat_next;
-                }
-                sat
+            Ctl::Ef(a) => self.backward_reach(self.fair(self.sat_ctl(a)), &self.states),
+            Ctl::Af(a) => {
+                let sat_a = self.sat_ctl(a);
+                let not_a = self.states.difference(&sat_a).cloned().collect();
+                let sat_eg = self.sat_eg(&not_a);
+                self.states.difference(&sat_eg).cloned().collect()
             }
-            Mu::Ex(act, a) => {
-                let sat_a = self.sat(a, env);
-                let mut sat_ex = HashSet::new();
-                for s1 in &self.states {
-                    if self.succ(s1, act).iter().any(|s2| sat_a.contains(s2)) {
-                        sat_ex.insert(*s1);
-                    }
-                }
-                sat_ex
+            Ctl::Eg(a) => self.sat_eg(&self.sat_ctl(a)),
+            Ctl::Ag(a) => {
+                let sat_a = self.sat_ctl(a);
+                let not_a = self.fair(self.states.difference(&sat_a).cloned().collect());
+                let sat_ef = self.backward_reach(not_a, &self.states);
+                self.states.difference(&sat_ef).cloned().collect()
+            }
+            Ctl::Eu(a, b) => self.backward_reach(self.fair(self.sat_ctl(b)), &self.sat_ctl(a)),
+            Ctl::Au(a, b) => {
+                // A[a U b] = ¬(E[¬b U (¬a ∧ ¬b)] ∨ EG ¬b)
+                let sat_a = self.sat_ctl(a);
+                let sat_b = self.sat_ctl(b);
+                let not_b: HashSet<u32> = self.states.difference(&sat_b).cloned().collect();
+                let bad = self.fair(not_b.difference(&sat_a).cloned().collect());
+                let mut sat_bad = self.backward_reach(bad, &not_b);
+                sat_bad.extend(self.sat_eg(&not_b));
+                self.states.difference(&sat_bad).cloned().collect()
+            }
+        }
+    }
+
+    /// Compute the set of states satisfying a formula,
+    /// given the value of its free variables.
+    ///
+    /// Nested fixpoints are computed with the Emerson–Lei optimisation:
+    /// when a fixpoint is evaluated again, its iteration restarts from its
+    /// previous value, unless an enclosing fixpoint of the opposite kind
+    /// has changed since. The kinds of the fixpoints are only meaningful in
+    /// positive normal form, to which the formula is translated first.
+    pub fn sat(&self, spec: &Mu<A, P>, env: HashMap<String, HashSet<u32>>) -> HashSet<u32> {
+        let mut env = env
+            .into_iter()
+            .map(|(x, states)| (x, self.transitions.to_bits(&states)))
+            .collect();
+        let sat = self.sat_el(&spec.to_pnf(), &mut env, &mut Approximants::default());
+        self.transitions.to_states(&sat)
+    }
+
+    fn sat_fixpoint(
+        &self,
+        spec: &Mu<A, P>,
+        env: &mut HashMap<String, BitSet>,
+        approx: &mut Approximants,
+    ) -> BitSet {
+        let n = self.transitions.len();
+        let (x, a, init) = match spec {
+            Mu::Lfp(x, a) => (x, a, BitSet::new(n)),
+            Mu::Gfp(x, a) => (x, a, BitSet::full(n)),
+            _ => unreachable!(),
+        };
+        let key = Approximants::key(spec);
+        let mut sat = approx.values.remove(&key).unwrap_or(init);
+        let outer = env.remove(x);
+        loop {
+            approx.reset_alternating(spec);
+            env.insert(x.clone(), sat.clone());
+            let sat_next = self.sat_el(a, env, approx);
+            if sat_next == sat {
+                break;
+            }
+            sat = sat_next;
+        }
+        match outer {
+            Some(outer) => env.insert(x.clone(), outer),
+            None => env.remove(x),
+        };
+        approx.values.insert(key, sat.clone());
+        sat
+    }
+
+    /// The indices of the states satisfying a formula in [`Ts::transitions`]
+    fn sat_el(
+        &self,
+        spec: &Mu<A, P>,
+        env: &mut HashMap<String, BitSet>,
+        approx: &mut Approximants,
+    ) -> BitSet {
+        let trans = &self.transitions;
+        match spec {
+            Mu::Lit(p) => self
+                .labels
+                .get(p)
+                .cloned()
+                .unwrap_or_else(|| BitSet::new(trans.len())),
+            Mu::Neg(a) => self.sat_el(a, env, approx).complement(),
+            Mu::And(a, b) => {
+                let mut sat_a = self.sat_el(a, env, approx);
+                sat_a.intersect_with(&self.sat_el(b, env, approx));
+                sat_a
+            }
+            Mu::Or(a, b) => {
+                let mut sat_a = self.sat_el(a, env, approx);
+                sat_a.union_with(&self.sat_el(b, env, approx));
+                sat_a
+            }
+            Mu::Gfp(_, _) | Mu::Lfp(_, _) => self.sat_fixpoint(spec, env, approx),
+            Mu::All(act, a) | Mu::AllPred(act, a) => {
+                let sat_a = self.sat_el(a, env, approx);
+                let converse = matches!(spec, Mu::AllPred(_, _));
+                trans.all(&trans.matching(act), &sat_a, converse)
+            }
+            Mu::Ex(act, a) | Mu::ExPred(act, a) => {
+                let sat_a = self.sat_el(a, env, approx);
+                let converse = matches!(spec, Mu::ExPred(_, _));
+                trans.ex(&trans.matching(act), &sat_a, converse)
             }
             Mu::Var(x) => env.get(x).unwrap().clone(),
         }
     }
 }
 
-impl<A, P> Display for Ts<A, P>
-where
-    A: Display,
-    A: Display,
-    A: Eq,
-    A: Clone,
-    A: Hash,
-    P: Eq,
-    P: Display,
-    P: Clone,
-    P: Hash,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "digraph {{")?;
-        writeln!(f, "  node [shape=circle]")?;
-        for x in self.states.iter() {
-            if self.initial.contains(x) {
-                writeln!(
-                    f,
-                    "  {} [shape=doublecircle, label=\"{{{}}}\"]",
-                    x,
-                    self.label(x)
-                        .iter()
-                        .map(|p| format!("{}", p))
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                )?
+/// Last approximants of the fixpoints of a formula,
+/// identified by their address in the formula
+#[derive(Default)]
+struct Approximants {
+    values: HashMap<usize, BitSet>,
+    /// Fixpoints nested in a fixpoint of the opposite kind
+    alternating: HashMap<usize, Vec<usize>>,
+}
+
+impl Approximants {
+    fn key<A, P>(spec: &Mu<A, P>) -> usize
+    where
+        A: Display + Clone,
+        P: Display,
+    {
+        spec as *const Mu<A, P> as usize
+    }
+
+    fn collect_alternating<A, P>(spec: &Mu<A, P>, lfp: bool, acc: &mut Vec<usize>)
+    where
+        A: Display + Clone,
+        P: Display,
+    {
+        match spec {
+            Mu::Lit(_) | Mu::Var(_) => (),
+            Mu::Neg(a) | Mu::All(_, a) | Mu::Ex(_, a) | Mu::AllPred(_, a) | Mu::ExPred(_, a) => {
+                Self::collect_alternating(a, lfp, acc)
+            }
+            Mu::And(a, b) | Mu::Or(a, b) => {
+                Self::collect_alternating(a, lfp, acc);
+                Self::collect_alternating(b, lfp, acc);
+            }
+            Mu::Lfp(_, a) | Mu::Gfp(_, a) => {
+                if matches!(spec, Mu::Lfp(_, _)) != lfp {
+                    acc.push(Self::key(spec));
+                }
+                Self::collect_alternating(a, lfp, acc);
             }
         }
-        for (x, post) in self.transitions.iter() {
-            for (a, y) in post {
-                writeln!(f, "  {} -> {} [label=\" {}\"];", x, y, a)?
+    }
+
+    /// Forget the approximants of the fixpoints nested in `spec`
+    /// whose kind is opposite to the one of `spec`
+    fn reset_alternating<A, P>(&mut self, spec: &Mu<A, P>)
+    where
+        A: Display + Clone,
+        P: Display,
+    {
+        let alternating = self.alternating.entry(Self::key(spec)).or_insert_with(|| {
+            let mut acc = vec![];
+            if let Mu::Lfp(_, a) | Mu::Gfp(_, a) = spec {
+                Self::collect_alternating(a, matches!(spec, Mu::Lfp(_, _)), &mut acc);
             }
+            acc
+        });
+        for key in alternating.iter() {
+            self.values.remove(key);
         }
-        writeln!(f, "}}")
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::collections::HashMap;
-
     use super::*;
 
     #[test]
@@ -189,7 +456,7 @@ mod tests {
             vec![1],
             vec![(2, vec!['A'])],
             vec![(1, vec![('a', 2)])],
-            vec![Mu::All('b', Box::new(Mu::Lit('A')))],
+            vec![Mu::All(Act::One('b'), Box::new(Mu::Lit('A')))],
         );
         assert!(ts.check())
     }
@@ -201,7 +468,7 @@ mod tests {
             vec![1],
             vec![(2, vec!['A'])],
             vec![(1, vec![('a', 2)])],
-            vec![Mu::All('a', Box::new(Mu::Lit('A')))],
+            vec![Mu::All(Act::One('a'), Box::new(Mu::Lit('A')))],
         );
         assert!(ts.check())
     }
@@ -213,7 +480,7 @@ mod tests {
             vec![1],
             vec![(1, vec!['A'])],
             vec![(1, vec![('a', 2)])],
-            vec![Mu::All('a', Box::new(Mu::Lit('A')))],
+            vec![Mu::All(Act::One('a'), Box::new(Mu::Lit('A')))],
         );
         assert!(!ts.check())
     }
@@ -225,7 +492,7 @@ mod tests {
             vec![1],
             vec![(1, vec!['A'])],
             vec![(1, vec![('a', 2)])],
-            vec![Mu::Ex('a', Box::new(Mu::Lit('A')))],
+            vec![Mu::Ex(Act::One('a'), Box::new(Mu::Lit('A')))],
         );
         assert!(!ts.check())
     }
@@ -237,7 +504,7 @@ mod tests {
             vec![1],
             vec![(1, vec!['A'])],
             vec![(1, vec![('a', 2)])],
-            vec![Mu::Ex('b', Box::new(Mu::Lit('A')))],
+            vec![Mu::Ex(Act::One('b'), Box::new(Mu::Lit('A')))],
         );
         assert!(!ts.check())
     }
@@ -249,7 +516,7 @@ mod tests {
             vec![1],
             vec![(3, vec!['A'])],
             vec![(1, vec![('a', 2), ('b', 3)])],
-            vec![Mu::Ex('b', Box::new(Mu::Lit('A')))],
+            vec![Mu::Ex(Act::One('b'), Box::new(Mu::Lit('A')))],
         );
         assert!(ts.check())
     }
@@ -293,4 +560,245 @@ mod tests {
         );
         assert!(ts.check());
     }
+
+    /// A system with a cycle, a deadlock and a self loop
+    fn ctl_system() -> Ts<char, char> {
+        Ts::new(
+            vec![1, 2, 3, 4, 5],
+            vec![1],
+            vec![
+                (1, vec!['A']),
+                (2, vec!['A', 'B']),
+                (3, vec!['B']),
+                (5, vec!['A']),
+            ],
+            vec![
+                (1, vec![('a', 2), ('b', 4)]),
+                (2, vec![('a', 3)]),
+                (3, vec![('a', 2), ('b', 5)]),
+                (5, vec![('a', 5)]),
+            ],
+            Vec::<Mu<char, char>>::new(),
+        )
+    }
+
+    fn ctl_formulas() -> Vec<Ctl<char, char>> {
+        let a = || Box::new(Ctl::Lit('A'));
+        let b = || Box::new(Ctl::Lit('B'));
+        vec![
+            Ctl::Ex(None, b()),
+            Ctl::Ex(Some('b'), a()),
+            Ctl::Ax(None, a()),
+            Ctl::Ef(b()),
+            Ctl::Af(b()),
+            Ctl::Eg(a()),
+            Ctl::Eg(b()),
+            Ctl::Ag(a()),
+            Ctl::Eu(a(), b()),
+            Ctl::Au(a(), b()),
+            Ctl::Au(b(), a()),
+            Ctl::Ag(Box::new(Ctl::Ef(b()))),
+            Ctl::Neg(Box::new(Ctl::Eg(Box::new(Ctl::Neg(b()))))),
+        ]
+    }
+
+    #[test]
+    fn test_ctl_1() {
+        let ts = ctl_system();
+        for ctl in ctl_formulas() {
+            let mu = ctl.to_mu();
+            assert_eq!(ts.sat_ctl(&ctl), ts.sat(&mu, HashMap::new()), "{}", ctl);
+            assert_eq!(ts.sat_ctl(&ctl), ts.sat_game(&mu), "{}", ctl);
+        }
+    }
+
+    #[test]
+    fn test_ctl_2() {
+        let ts = ctl_system();
+        let eg = Ctl::Eg(Box::new(Ctl::Lit('A')));
+        assert_eq!(ts.sat_ctl(&eg), HashSet::from([5]));
+        let af = Ctl::Af(Box::new(Ctl::Lit('B')));
+        assert_eq!(ts.sat_ctl(&af), HashSet::from([2, 3]));
+    }
+
+    #[test]
+    fn test_9() {
+        let ts = Ts::new(
+            vec![1, 2, 3],
+            vec![1],
+            vec![(2, vec!['A'])],
+            vec![(1, vec![('a', 2), ('a', 3)])],
+            vec![Mu::Ex(Act::One('a'), Box::new(Mu::Lit('A')))],
+        );
+        assert!(ts.check());
+        let all = Mu::All(Act::One('a'), Box::new(Mu::Lit('A')));
+        assert_eq!(ts.sat(&all, HashMap::new()), HashSet::from([2, 3]));
+        assert_eq!(ts.succ(&1, &'a').count(), 2);
+    }
+
+    #[test]
+    fn test_nested_fixpoints() {
+        let ts = ctl_system();
+        let var = |x: &str| Box::new(Mu::Var(x.to_string()));
+        let any = |mu: Box<Mu<char, char>>| {
+            Box::new(Mu::Or(
+                Box::new(Mu::Ex(Act::One('a'), mu.clone())),
+                Box::new(Mu::Ex(Act::One('b'), mu)),
+            ))
+        };
+        // νX. μY. (B ∧ ◇X) ∨ ◇Y: B holds infinitely often on some path
+        let inf_b = Mu::Gfp(
+            "X".to_string(),
+            Box::new(Mu::Lfp(
+                "Y".to_string(),
+                Box::new(Mu::Or(
+                    Box::new(Mu::And(Box::new(Mu::Lit('B')), any(var("X")))),
+                    any(var("Y")),
+                )),
+            )),
+        );
+        // μX. νY. (A ∧ ◇Y) ∨ ◇X: some path eventually stays in A
+        let fg_a = Mu::Lfp(
+            "X".to_string(),
+            Box::new(Mu::Gfp(
+                "Y".to_string(),
+                Box::new(Mu::Or(
+                    Box::new(Mu::And(Box::new(Mu::Lit('A')), any(var("Y")))),
+                    any(var("X")),
+                )),
+            )),
+        );
+        // μX. μY. B ∨ ◇X ∨ ◇Y
+        let ef_b = Mu::Lfp(
+            "X".to_string(),
+            Box::new(Mu::Lfp(
+                "Y".to_string(),
+                Box::new(Mu::Or(
+                    Box::new(Mu::Lit('B')),
+                    Box::new(Mu::Or(any(var("X")), any(var("Y")))),
+                )),
+            )),
+        );
+        assert_eq!(ts.sat(&inf_b, HashMap::new()), HashSet::from([1, 2, 3]));
+        assert_eq!(ts.sat(&fg_a, HashMap::new()), HashSet::from([1, 2, 3, 5]));
+        assert_eq!(ts.sat(&ef_b, HashMap::new()), HashSet::from([1, 2, 3]));
+        let nested = Mu::Gfp(
+            "Z".to_string(),
+            Box::new(Mu::And(
+                Box::new(fg_a),
+                Box::new(Mu::And(Box::new(inf_b), any(var("Z")))),
+            )),
+        );
+        assert_eq!(ts.sat(&nested, HashMap::new()), ts.sat_game(&nested));
+    }
+
+    #[test]
+    fn test_negated_fixpoints() {
+        let ts = Ts::new(
+            vec![1, 2, 3],
+            vec![3],
+            vec![(1, vec!['P'])],
+            vec![(3, vec![('a', 2)]), (2, vec![('a', 1)])],
+            Vec::<Mu<char, char>>::new(),
+        );
+        let neg = |mu| Box::new(Mu::Neg(mu));
+        // μX. ¬(μY. (¬P ∧ [a]¬X) ∨ Y): the inner fixpoint is a greatest one
+        let spec = Mu::Lfp(
+            "X".to_string(),
+            neg(Box::new(Mu::Lfp(
+                "Y".to_string(),
+                Box::new(Mu::Or(
+                    Box::new(Mu::And(
+                        neg(Box::new(Mu::Lit('P'))),
+                        Box::new(Mu::All(
+                            Act::One('a'),
+                            neg(Box::new(Mu::Var("X".to_string()))),
+                        )),
+                    )),
+                    Box::new(Mu::Var("Y".to_string())),
+                )),
+            ))),
+        );
+        assert_eq!(ts.sat(&spec, HashMap::new()), HashSet::from([1, 2, 3]));
+        assert_eq!(ts.sat(&spec, HashMap::new()), ts.sat_game(&spec));
+    }
+
+    #[test]
+    fn test_10() {
+        let ts = ctl_system();
+        let lit = || Box::new(Mu::Lit('A'));
+        let set = Mu::Ex(Act::Set(vec!['a', 'b']), lit());
+        let or = Mu::Or(
+            Box::new(Mu::Ex(Act::One('a'), lit())),
+            Box::new(Mu::Ex(Act::One('b'), lit())),
+        );
+        assert_eq!(ts.sat(&set, HashMap::new()), ts.sat(&or, HashMap::new()));
+        assert_eq!(
+            ts.sat(&Mu::Ex(Act::True, lit()), HashMap::new()),
+            ts.sat(&or, HashMap::new())
+        );
+        let not_a = Mu::All(Act::Not(Box::new(Act::One('a'))), lit());
+        let b = Mu::All(Act::One('b'), lit());
+        assert_eq!(ts.sat(&not_a, HashMap::new()), ts.sat(&b, HashMap::new()));
+        assert_eq!(ts.sat(&not_a, HashMap::new()), ts.sat_game(&not_a));
+    }
+
+    #[test]
+    fn test_11() {
+        // 1 -login-> 2 -auth-> 3 (Secret), 1 -guest-> 4, 5 -guest-> 3 (5 unreachable, Init in 1)
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2, 3, 4, 5],
+            vec![1],
+            vec![(1, vec!['I']), (3, vec!['S'])],
+            vec![
+                (1, vec![('l', 2), ('g', 4)]),
+                (2, vec![('a', 3)]),
+                (5, vec![('g', 3)]),
+            ],
+            vec![],
+        );
+        // Secret states are only entered through auth steps
+        let only_auth = Mu::Or(
+            Box::new(Mu::Neg(Box::new(Mu::Lit('S')))),
+            Box::new(Mu::AllPred(
+                Act::Not(Box::new(Act::One('a'))),
+                Box::new(Mu::Lit('E')),
+            )),
+        );
+        assert_eq!(
+            ts.sat(&only_auth, HashMap::new()),
+            HashSet::from([1, 2, 4, 5])
+        );
+        let reachable = Mu::Lfp(
+            "X".to_string(),
+            Box::new(Mu::Or(
+                Box::new(Mu::Lit('I')),
+                Box::new(Mu::ExPred(Act::True, Box::new(Mu::Var("X".to_string())))),
+            )),
+        );
+        assert_eq!(
+            ts.sat(&reachable, HashMap::new()),
+            HashSet::from([1, 2, 3, 4])
+        );
+        assert_eq!(ts.sat(&reachable, HashMap::new()), ts.sat_game(&reachable));
+        let neg = Mu::Neg(Box::new(only_auth.clone()));
+        assert_eq!(ts.sat(&neg.to_pnf(), HashMap::new()), HashSet::from([3]));
+        assert!(!ts.explain(&Spec::Mu(only_auth), 3).holds);
+    }
+
+    #[test]
+    fn test_12() {
+        // The transition to the undeclared state 3 is ignored
+        let ts = Ts::new::<Spec<char, char>>(
+            vec![1, 2],
+            vec![1],
+            vec![(2, vec!['A'])],
+            vec![(1, vec![('a', 2), ('a', 3)])],
+            vec![],
+        );
+        assert_eq!(ts.succ(&1, &'a').collect::<Vec<_>>(), vec![&2]);
+        let not_a = Mu::Neg(Box::new(Mu::Lit('A')));
+        assert_eq!(ts.sat(&not_a, HashMap::new()), HashSet::from([1]));
+        assert_eq!(ts.label(&2), HashSet::from(['A']));
+    }
 }
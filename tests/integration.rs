@@ -1,4 +1,75 @@
+use std::fs;
+
+use mcmu::lang::Prog;
+
+fn check(input: &str) -> bool {
+    input.parse::<Prog>().unwrap().compile().unwrap().check()
+}
+
+#[test]
+pub fn test_model_input() {
+    let input = fs::read_to_string("data/model.input").unwrap();
+    assert!(check(&input))
+}
+
+#[test]
+pub fn test_ctl() {
+    let input = "
+(props P Q)
+(actions a b)
+(init 1)
+(label 1 P)
+(label 2 P)
+(label 3 Q)
+(trans 1 a 2)
+(trans 1 b 3)
+(loop 2 a)
+(loop 3 a)
+(spec (ef Q))
+(spec (eg P))
+(spec (not (af Q)))
+(spec (ex (b) Q))
+(spec (ax (or P Q)))
+(spec (eu P Q))
+(spec (not (au P Q)))";
+    assert!(check(input))
+}
+
+#[test]
+pub fn test_ctl_violated() {
+    let input = "
+(props P Q)
+(actions a)
+(init 1)
+(label 1 P)
+(label 2 Q)
+(trans 1 a 2)
+(loop 2 a)
+(spec (ag P))";
+    assert!(!check(input))
+}
+
+#[test]
+pub fn test_ltl() {
+    let input = "
+(props Req Grant)
+(actions req grant idle)
+(init 1)
+(label 2 Req)
+(label 3 Grant)
+(trans 1 req 2)
+(trans 2 grant 3)
+(trans 3 idle 1)
+(loop 1 idle)
+(spec (g (or (not Req) (f Grant))))
+(spec (g (or (not Req) (x Grant))))
+(spec (not (f (and Req Grant))))
+(spec (r Req (not Grant)))";
+    assert!(check(input))
+}
+
 #[test]
+#[allow(clippy::assertions_on_constants)]
 pub fn test() {
     assert!(true)
 }